@@ -0,0 +1,278 @@
+use std::{
+    marker::PhantomData,
+    net::{SocketAddr, SocketAddrV6, UdpSocket},
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    protocol::{Header, Probe},
+    sockets::{bind_v4, bind_v6, broadcast_addresses, ipv6_interfaces, receive},
+    DiscoverySettings, Payload,
+};
+
+/// An event that is sent when a new server is found
+#[derive(Debug, Event)]
+pub struct FoundNewServerEvent<P: Payload> {
+    pub server: DiscoveredServer<P>,
+}
+
+/// An event that is sent when a server stops answering
+#[derive(Debug, Event)]
+pub struct ServerLostEvent<P: Payload> {
+    pub addr: SocketAddr,
+    _payload: PhantomData<fn() -> P>,
+}
+
+/// A server that answered the discovery
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiscoveredServer<P> {
+    /// Address the server answered from, IPv6 link-local addresses keep the scope of the interface.
+    pub addr: SocketAddr,
+    pub payload: P,
+    /// Round trip time of the last probe answered.
+    pub ping: Duration,
+    /// Sequence of the last probe answered.
+    last_sequence: u32,
+}
+
+/// The servers found, the client probes the network while this resource exists.
+#[derive(Debug, Resource)]
+pub struct DiscoverServers<P: Payload> {
+    servers: Vec<DiscoveredServer<P>>,
+}
+
+impl<P: Payload> Default for DiscoverServers<P> {
+    fn default() -> Self {
+        Self {
+            servers: Vec::new(),
+        }
+    }
+}
+
+impl<P: Payload> DiscoverServers<P> {
+    /// Adds a server to the list of servers found, or refreshes its payload if it's known
+    ///
+    /// Returns `true` if the server is new.
+    fn add_server(&mut self, server: DiscoveredServer<P>) -> bool {
+        match self
+            .servers
+            .iter_mut()
+            .find(|known| known.addr == server.addr)
+        {
+            Some(known) => {
+                // Replies to older probes may arrive late, they don't tell anything new
+                if server.last_sequence >= known.last_sequence {
+                    *known = server;
+                }
+                false
+            }
+            None => {
+                self.servers.push(server);
+                true
+            }
+        }
+    }
+
+    /// Removes the servers that didn't answer the probes after `last_sequence - max_missed`
+    ///
+    /// Returns the addresses of the removed servers.
+    fn remove_lost(&mut self, last_sequence: u32, max_missed: u32) -> Vec<SocketAddr> {
+        let mut lost = Vec::new();
+        self.servers.retain(|server| {
+            let missed = last_sequence.wrapping_sub(server.last_sequence);
+            if missed >= max_missed {
+                lost.push(server.addr);
+            }
+            missed < max_missed
+        });
+        lost
+    }
+
+    /// Returns an iterator over the servers found
+    pub fn servers(&self) -> impl Iterator<Item = &DiscoveredServer<P>> {
+        self.servers.iter()
+    }
+
+    /// Returns the server found at `addr`
+    pub fn get(&self, addr: SocketAddr) -> Option<&DiscoveredServer<P>> {
+        self.servers.iter().find(|server| server.addr == addr)
+    }
+
+    /// Forgets the servers found, they are added back as they answer
+    pub fn clear(&mut self) {
+        self.servers.clear();
+    }
+}
+
+/// The discovery client
+#[derive(Debug, Resource)]
+pub(crate) struct DiscoveryClient<P> {
+    socket: UdpSocket,
+    /// Sends the probes to the IPv6 multicast group, `None` without IPv6
+    socket_v6: Option<UdpSocket>,
+    header: Header,
+    /// Probe timestamps are measured from here
+    started: Instant,
+    /// Sequence of the last probe sent
+    sequence: u32,
+    probe_timer: Timer,
+    _payload: PhantomData<fn() -> P>,
+}
+
+impl<P> DiscoveryClient<P> {
+    /// Broadcasts a probe with the next sequence number on every interface
+    ///
+    /// The interfaces are listed again for each probe, so the ones that come up later are probed too.
+    fn send_probe(&mut self, settings: &DiscoverySettings<P>) -> anyhow::Result<()> {
+        self.sequence = self.sequence.wrapping_add(1);
+        let probe = Probe {
+            sequence: self.sequence,
+            timestamp: self.started.elapsed(),
+        };
+        let message = self.header.encode_probe(&probe)?;
+        let mut targets: Vec<SocketAddr> = broadcast_addresses()
+            .into_iter()
+            .map(|broadcast| (broadcast, settings.port).into())
+            .collect();
+        if self.socket_v6.is_some() {
+            targets.extend(ipv6_interfaces().into_iter().map(|index| {
+                SocketAddr::from(SocketAddrV6::new(
+                    settings.multicast_group,
+                    settings.port,
+                    0,
+                    index,
+                ))
+            }));
+        }
+        targets.extend(&settings.targets);
+
+        let mut sent = false;
+        for target in targets {
+            let socket = match target {
+                SocketAddr::V4(_) => Some(&self.socket),
+                SocketAddr::V6(_) => self.socket_v6.as_ref(),
+            };
+            match socket.map(|socket| socket.send_to(&message, target)) {
+                Some(Ok(_)) => sent = true,
+                Some(Err(e)) => debug!("Failed to send discovery request to {}: {}", target, e),
+                None => {}
+            }
+        }
+        anyhow::ensure!(sent, "no interface to send the discovery request on");
+        Ok(())
+    }
+}
+
+/// Starts the discovery client
+pub(crate) fn start_discovery_client<P: Payload>(
+    mut commands: Commands,
+    settings: Res<DiscoverySettings<P>>,
+) {
+    info!("Starting discovery client");
+    let socket = match bind_v4(0).and_then(|socket| {
+        socket.set_broadcast(true)?;
+        Ok(socket)
+    }) {
+        Ok(socket) => socket,
+        Err(e) => {
+            error!("Failed to start discovery client: {}", e);
+            return;
+        }
+    };
+    let socket_v6 = bind_v6(0, None)
+        .inspect_err(|e| warn!("IPv6 discovery unavailable: {}", e))
+        .ok();
+    let mut client = DiscoveryClient {
+        socket,
+        socket_v6,
+        header: Header::new(&settings),
+        started: Instant::now(),
+        sequence: 0,
+        probe_timer: Timer::new(settings.probe_interval, TimerMode::Repeating),
+        _payload: PhantomData,
+    };
+    // Send a discovery message to the broadcast address
+    client
+        .send_probe(&settings)
+        .inspect_err(|e| error!("Failed to send discovery request: {}", e))
+        .ok();
+    commands.insert_resource(client);
+}
+
+/// Handles messages received by the discovery client
+pub(crate) fn handle_client_messages<P: Payload>(
+    client: Res<DiscoveryClient<P>>,
+    mut discover_servers: ResMut<DiscoverServers<P>>,
+    mut event: EventWriter<FoundNewServerEvent<P>>,
+) {
+    let mut buf = [0; 1024];
+    let sockets = std::iter::once(&client.socket).chain(&client.socket_v6);
+    while let Some((size, addr)) = sockets.clone().find_map(|socket| receive(socket, &mut buf)) {
+        let Some(reply) = client.header.decode_reply::<P>(&buf[..size]) else {
+            continue;
+        };
+        let server = DiscoveredServer {
+            addr,
+            payload: reply.payload,
+            ping: client
+                .started
+                .elapsed()
+                .saturating_sub(reply.probe.timestamp),
+            last_sequence: reply.probe.sequence,
+        };
+        if discover_servers.add_server(server.clone()) {
+            info!("Received discovery response from {}", addr);
+            // Send an event to notify that a new server was found
+            event.send(FoundNewServerEvent { server });
+        }
+    }
+}
+
+/// Sends a probe every [`DiscoverySettings::probe_interval`]
+///
+/// Before that, the servers that missed too many probes are removed.
+pub(crate) fn send_probes<P: Payload>(
+    time: Res<Time>,
+    settings: Res<DiscoverySettings<P>>,
+    mut client: ResMut<DiscoveryClient<P>>,
+    mut discover_servers: ResMut<DiscoverServers<P>>,
+    mut event: EventWriter<ServerLostEvent<P>>,
+) {
+    if settings.is_changed() {
+        client.probe_timer.set_duration(settings.probe_interval);
+    }
+    if !client.probe_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+    // Only flag the resource as changed when a server is removed
+    let lost = discover_servers
+        .bypass_change_detection()
+        .remove_lost(client.sequence, settings.max_missed_replies);
+    if !lost.is_empty() {
+        discover_servers.set_changed();
+    }
+    for addr in lost {
+        info!("Server {} stopped answering", addr);
+        event.send(ServerLostEvent {
+            addr,
+            _payload: PhantomData,
+        });
+    }
+    // If there are multiple servers, we keep storing them on the DiscoverServers resource.
+    // This allows the UI to show the list of servers found.
+    client
+        .send_probe(&settings)
+        .inspect_err(|e| error!("Failed to send discovery request: {}", e))
+        .ok();
+}
+
+/// Stops the discovery client
+///
+/// This just removes the resource, which will cause
+/// the client to stop due to drop
+pub(crate) fn stop_discovery_client<P: Payload>(mut commands: Commands) {
+    info!("Stopping discovery client");
+    commands.remove_resource::<DiscoveryClient<P>>()
+}
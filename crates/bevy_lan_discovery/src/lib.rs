@@ -0,0 +1,175 @@
+//! Finds the servers of a Bevy game on the local network.
+//!
+//! Servers advertise a payload of your choice by inserting an [`Advertisement`] resource,
+//! clients collect the payloads of the servers that answer in a [`DiscoverServers`] resource.
+//! Probes are broadcast on every IPv4 interface and sent to a link-local multicast group on
+//! every IPv6 interface.
+//!
+//! ```no_run
+//! use bevy::prelude::*;
+//! use bevy_lan_discovery::{Advertisement, DiscoverServers, DiscoveryPlugin};
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Clone, Serialize, Deserialize)]
+//! struct Lobby {
+//!     name: String,
+//! }
+//!
+//! let mut app = App::new();
+//! app.add_plugins(DiscoveryPlugin::<Lobby>::new("MY_GAME").port(53005));
+//! // On the server
+//! app.insert_resource(Advertisement(Lobby { name: "Lobby".to_string() }));
+//! // On the client
+//! app.insert_resource(DiscoverServers::<Lobby>::default());
+//! ```
+
+mod client;
+mod protocol;
+mod server;
+mod sockets;
+
+use std::{
+    marker::PhantomData,
+    net::{Ipv6Addr, SocketAddr},
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+pub use client::{DiscoverServers, DiscoveredServer, FoundNewServerEvent, ServerLostEvent};
+pub use server::Advertisement;
+
+/// What servers advertise, any serializable type can be used.
+pub trait Payload: Serialize + DeserializeOwned + Clone + Send + Sync + 'static {}
+
+impl<T: Serialize + DeserializeOwned + Clone + Send + Sync + 'static> Payload for T {}
+
+/// Answers probes while an [`Advertisement<P>`] exists, and probes the network while a
+/// [`DiscoverServers<P>`] exists.
+///
+/// Several plugins can be added as long as their payloads are different types.
+pub struct DiscoveryPlugin<P> {
+    settings: DiscoverySettings<P>,
+}
+
+impl<P: Payload> DiscoveryPlugin<P> {
+    /// Creates the plugin, only servers with the same `app_id` are discovered.
+    pub fn new(app_id: impl Into<String>) -> Self {
+        Self {
+            settings: DiscoverySettings {
+                app_id: app_id.into(),
+                port: 53005,
+                version: 1,
+                multicast_group: Ipv6Addr::new(0xff02, 0, 0, 0, 0, 0, 0x7474, 0x7474),
+                probe_interval: Duration::from_secs(1),
+                max_missed_replies: 3,
+                targets: Vec::new(),
+                _payload: PhantomData,
+            },
+        }
+    }
+
+    /// Port the servers listen to probes on, the default is 53005.
+    pub fn port(mut self, port: u16) -> Self {
+        self.settings.port = port;
+        self
+    }
+
+    /// Version of the payload, servers with another version are ignored.
+    ///
+    /// Bump it when the payload type changes.
+    pub fn version(mut self, version: u16) -> Self {
+        self.settings.version = version;
+        self
+    }
+
+    /// The IPv6 link-local multicast group, the default is `ff02::7474:7474`.
+    pub fn multicast_group(mut self, group: Ipv6Addr) -> Self {
+        self.settings.multicast_group = group;
+        self
+    }
+
+    /// Time between two probes, the default is one second.
+    pub fn probe_interval(mut self, interval: Duration) -> Self {
+        self.settings.probe_interval = interval;
+        self
+    }
+
+    /// A server that didn't answer this many probes in a row is lost, the default is 3.
+    pub fn max_missed_replies(mut self, max_missed_replies: u32) -> Self {
+        self.settings.max_missed_replies = max_missed_replies;
+        self
+    }
+
+    /// Also probes this address directly, for hosts that broadcasts don't reach.
+    pub fn target(mut self, target: SocketAddr) -> Self {
+        self.settings.targets.push(target);
+        self
+    }
+}
+
+impl<P: Payload> Plugin for DiscoveryPlugin<P> {
+    fn build(&self, app: &mut App) {
+        {
+            app.insert_resource(self.settings.clone());
+            app.add_event::<FoundNewServerEvent<P>>();
+            app.add_event::<ServerLostEvent<P>>();
+            app.add_systems(
+                Update,
+                (
+                    server::start_discovery_server::<P>.run_if(resource_added::<Advertisement<P>>),
+                    server::handle_server_messages::<P>
+                        .run_if(resource_exists::<server::DiscoveryServer<P>>),
+                    server::stop_discovery_server::<P>
+                        .run_if(not(resource_exists::<Advertisement<P>>))
+                        .run_if(resource_exists::<server::DiscoveryServer<P>>),
+                )
+                    .chain(),
+            );
+            app.add_systems(
+                Update,
+                (
+                    client::start_discovery_client::<P>
+                        .run_if(resource_added::<DiscoverServers<P>>),
+                    (
+                        client::handle_client_messages::<P>,
+                        client::send_probes::<P>,
+                    )
+                        .run_if(resource_exists::<DiscoverServers<P>>)
+                        .run_if(resource_exists::<client::DiscoveryClient<P>>),
+                    client::stop_discovery_client::<P>
+                        .run_if(not(resource_exists::<DiscoverServers<P>>))
+                        .run_if(resource_exists::<client::DiscoveryClient<P>>),
+                )
+                    .chain(),
+            );
+        }
+    }
+}
+
+/// How the discovery of the servers advertising `P` works, set from [`DiscoveryPlugin`].
+///
+/// The interval and the number of missed replies can be changed at runtime,
+/// the other settings are used when the server or the client starts.
+#[derive(Debug, Resource)]
+pub struct DiscoverySettings<P> {
+    pub app_id: String,
+    pub port: u16,
+    pub version: u16,
+    pub multicast_group: Ipv6Addr,
+    pub probe_interval: Duration,
+    pub max_missed_replies: u32,
+    pub targets: Vec<SocketAddr>,
+    _payload: PhantomData<fn() -> P>,
+}
+
+impl<P> Clone for DiscoverySettings<P> {
+    fn clone(&self) -> Self {
+        Self {
+            app_id: self.app_id.clone(),
+            targets: self.targets.clone(),
+            ..*self
+        }
+    }
+}
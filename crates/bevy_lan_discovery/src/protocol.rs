@@ -0,0 +1,84 @@
+//! The messages exchanged by the clients and the servers.
+//!
+//! Every message starts with a magic string made from the app id, followed by the version
+//! of this protocol, the version of the payload, and the serialized content.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::DiscoverySettings;
+
+// Version of the messages, bump it when `Probe` or `Reply` change
+const PROTOCOL_VERSION: u16 = 1;
+
+/// Sent by the client, the server echoes it back in its reply
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub(crate) struct Probe {
+    pub sequence: u32,
+    /// Time since the client started, to measure the round trip
+    pub timestamp: Duration,
+}
+
+/// Sent by the server in response to a probe
+#[derive(Debug, Serialize, Deserialize)]
+pub(crate) struct Reply<P> {
+    pub probe: Probe,
+    pub payload: P,
+}
+
+/// The magic strings starting the messages of an app
+#[derive(Debug)]
+pub(crate) struct Header {
+    probe_magic: Vec<u8>,
+    reply_magic: Vec<u8>,
+    version: u16,
+}
+
+impl Header {
+    pub fn new<P>(settings: &DiscoverySettings<P>) -> Self {
+        Self {
+            probe_magic: format!("{}_DISCOVER", settings.app_id).into_bytes(),
+            reply_magic: format!("{}_FOUND", settings.app_id).into_bytes(),
+            version: settings.version,
+        }
+    }
+
+    pub fn encode_probe(&self, probe: &Probe) -> anyhow::Result<Vec<u8>> {
+        self.encode(&self.probe_magic, probe)
+    }
+
+    pub fn decode_probe(&self, message: &[u8]) -> Option<Probe> {
+        self.decode(&self.probe_magic, message)
+    }
+
+    pub fn encode_reply<P: Serialize>(&self, reply: &Reply<P>) -> anyhow::Result<Vec<u8>> {
+        self.encode(&self.reply_magic, reply)
+    }
+
+    pub fn decode_reply<P: DeserializeOwned>(&self, message: &[u8]) -> Option<Reply<P>> {
+        self.decode(&self.reply_magic, message)
+    }
+
+    fn encode(&self, magic: &[u8], content: &impl Serialize) -> anyhow::Result<Vec<u8>> {
+        let mut message = magic.to_vec();
+        message.extend_from_slice(&PROTOCOL_VERSION.to_le_bytes());
+        message.extend_from_slice(&self.version.to_le_bytes());
+        message.extend(bincode::serialize(content)?);
+        Ok(message)
+    }
+
+    /// `None` if the message isn't one, or was sent with another version
+    fn decode<T: DeserializeOwned>(&self, magic: &[u8], message: &[u8]) -> Option<T> {
+        let message = message.strip_prefix(magic)?;
+        let (versions, content) = message.split_at_checked(4)?;
+        let protocol_version = u16::from_le_bytes([versions[0], versions[1]]);
+        let version = u16::from_le_bytes([versions[2], versions[3]]);
+        if protocol_version != PROTOCOL_VERSION || version != self.version {
+            debug!("ignoring discovery message with another version");
+            return None;
+        }
+        bincode::deserialize(content).ok()
+    }
+}
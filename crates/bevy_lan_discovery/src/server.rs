@@ -0,0 +1,83 @@
+use std::{marker::PhantomData, net::UdpSocket};
+
+use bevy::prelude::*;
+
+use crate::{
+    protocol::{Header, Reply},
+    sockets::{bind_v4, bind_v6, receive},
+    DiscoverySettings, Payload,
+};
+
+/// What the server answers to the probes, the server runs while this resource exists.
+#[derive(Debug, Clone, Resource, Deref, DerefMut)]
+pub struct Advertisement<P: Payload>(pub P);
+
+/// The discovery server
+#[derive(Debug, Resource)]
+pub(crate) struct DiscoveryServer<P> {
+    socket: UdpSocket,
+    /// Receives the probes sent to the IPv6 multicast group, `None` without IPv6
+    socket_v6: Option<UdpSocket>,
+    header: Header,
+    _payload: PhantomData<fn() -> P>,
+}
+
+/// Starts the discovery server
+pub(crate) fn start_discovery_server<P: Payload>(
+    mut commands: Commands,
+    settings: Res<DiscoverySettings<P>>,
+) {
+    info!("Starting discovery on port {}", settings.port);
+    let socket = match bind_v4(settings.port) {
+        Ok(socket) => socket,
+        Err(e) => {
+            error!("Failed to start discovery: {}", e);
+            return;
+        }
+    };
+    let socket_v6 = bind_v6(settings.port, Some(settings.multicast_group))
+        .inspect_err(|e| warn!("IPv6 discovery unavailable: {}", e))
+        .ok();
+    commands.insert_resource(DiscoveryServer::<P> {
+        socket,
+        socket_v6,
+        header: Header::new(&settings),
+        _payload: PhantomData,
+    });
+}
+
+/// Answers the probes with the advertisement
+pub(crate) fn handle_server_messages<P: Payload>(
+    server: Res<DiscoveryServer<P>>,
+    advertisement: Option<Res<Advertisement<P>>>,
+) {
+    let mut buf = [0; 1024];
+    for socket in std::iter::once(&server.socket).chain(&server.socket_v6) {
+        while let Some((size, addr)) = receive(socket, &mut buf) {
+            let (Some(probe), Some(advertisement)) =
+                (server.header.decode_probe(&buf[..size]), &advertisement)
+            else {
+                continue;
+            };
+            let reply = Reply {
+                probe,
+                payload: &advertisement.0,
+            };
+            server
+                .header
+                .encode_reply(&reply)
+                .and_then(|reply| Ok(socket.send_to(&reply, addr)?))
+                .inspect_err(|e| error!("Failed to send discovery reply: {}", e))
+                .ok();
+        }
+    }
+}
+
+/// Stops the discovery server
+///
+/// This just removes the resource, which will cause
+/// the server to stop due to drop
+pub(crate) fn stop_discovery_server<P: Payload>(mut commands: Commands) {
+    info!("Stopping discovery");
+    commands.remove_resource::<DiscoveryServer<P>>();
+}
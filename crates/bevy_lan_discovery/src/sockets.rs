@@ -0,0 +1,80 @@
+//! Helpers over the sockets and the network interfaces.
+
+use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket};
+
+use bevy::prelude::*;
+use if_addrs::IfAddr;
+use socket2::{Domain, Protocol, Socket, Type};
+
+/// Reads the next datagram of a non blocking socket
+///
+/// Returns `None` once there's nothing left to read.
+pub(crate) fn receive(socket: &UdpSocket, buf: &mut [u8]) -> Option<(usize, SocketAddr)> {
+    match socket.recv_from(buf) {
+        Ok(received) => Some(received),
+        Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
+            // WouldBlock is expected when there's no data available yet
+            None
+        }
+        Err(e) => {
+            error!("Failed to receive message: {}", e);
+            None
+        }
+    }
+}
+
+/// The limited broadcast address and the directed broadcast address of every IPv4 interface
+///
+/// The limited broadcast only goes out of the default interface on some systems.
+pub(crate) fn broadcast_addresses() -> Vec<Ipv4Addr> {
+    let mut addresses = vec![Ipv4Addr::BROADCAST];
+    for interface in if_addrs::get_if_addrs().unwrap_or_default() {
+        if let IfAddr::V4(addr) = interface.addr {
+            if let Some(broadcast) = addr.broadcast.filter(|_| !addr.ip.is_loopback()) {
+                if !addresses.contains(&broadcast) {
+                    addresses.push(broadcast);
+                }
+            }
+        }
+    }
+    addresses
+}
+
+/// The index of every interface with an IPv6 address, to scope the link-local multicast
+pub(crate) fn ipv6_interfaces() -> Vec<u32> {
+    let mut indices = Vec::new();
+    for interface in if_addrs::get_if_addrs().unwrap_or_default() {
+        if let (IfAddr::V6(_), Some(index)) = (&interface.addr, interface.index) {
+            if !interface.is_loopback() && !indices.contains(&index) {
+                indices.push(index);
+            }
+        }
+    }
+    indices
+}
+
+/// Binds a non blocking IPv4 socket
+pub(crate) fn bind_v4(port: u16) -> anyhow::Result<UdpSocket> {
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port))?;
+    socket.set_nonblocking(true)?;
+    Ok(socket)
+}
+
+/// Binds a non blocking IPv6 socket, leaving IPv4 to the other socket bound to the same port
+///
+/// With a `group`, it joins that multicast group on every interface.
+pub(crate) fn bind_v6(port: u16, group: Option<Ipv6Addr>) -> anyhow::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV6, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_only_v6(true)?;
+    socket.bind(&SocketAddr::from((Ipv6Addr::UNSPECIFIED, port)).into())?;
+    if let Some(group) = group {
+        for index in ipv6_interfaces() {
+            socket
+                .join_multicast_v6(&group, index)
+                .inspect_err(|e| debug!("Failed to join multicast on interface {}: {}", index, e))
+                .ok();
+        }
+    }
+    socket.set_nonblocking(true)?;
+    Ok(socket.into())
+}
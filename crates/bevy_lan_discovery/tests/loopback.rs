@@ -0,0 +1,158 @@
+//! Runs a discovery server and a client in two apps, talking over loopback.
+
+use std::{
+    net::{Ipv4Addr, SocketAddr, UdpSocket},
+    thread,
+    time::Duration,
+};
+
+use bevy::prelude::*;
+use bevy_lan_discovery::{Advertisement, DiscoverServers, DiscoveryPlugin, ServerLostEvent};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Lobby {
+    name: String,
+    players: u8,
+}
+
+/// Servers lost by the client app, events only live for two updates.
+#[derive(Resource, Default)]
+struct LostServers(Vec<SocketAddr>);
+
+fn collect_lost_servers(
+    mut events: EventReader<ServerLostEvent<Lobby>>,
+    mut lost: ResMut<LostServers>,
+) {
+    lost.0.extend(events.read().map(|event| event.addr));
+}
+
+fn free_port() -> u16 {
+    UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+fn discovery_app(plugin: DiscoveryPlugin<Lobby>) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins);
+    app.add_plugins(plugin);
+    app.init_resource::<LostServers>();
+    app.add_systems(PostUpdate, collect_lost_servers);
+    app
+}
+
+/// A plugin probing the server on loopback, broadcasts may not leave a sandbox.
+fn plugin(app_id: &str, port: u16) -> DiscoveryPlugin<Lobby> {
+    DiscoveryPlugin::new(app_id)
+        .port(port)
+        .probe_interval(Duration::from_millis(20))
+        .max_missed_replies(3)
+        .target((Ipv4Addr::LOCALHOST, port).into())
+}
+
+fn lobby(players: u8) -> Lobby {
+    Lobby {
+        name: "Test lobby".to_string(),
+        players,
+    }
+}
+
+/// The server found by the client on loopback.
+fn loopback_server(client: &App) -> Option<&bevy_lan_discovery::DiscoveredServer<Lobby>> {
+    client
+        .world
+        .get_resource::<DiscoverServers<Lobby>>()?
+        .servers()
+        .find(|server| server.addr.ip() == Ipv4Addr::LOCALHOST)
+}
+
+/// Updates both apps until `condition` holds on the client, `false` if it never does.
+fn run_until(server: &mut App, client: &mut App, condition: impl Fn(&App) -> bool) -> bool {
+    for _ in 0..200 {
+        server.update();
+        client.update();
+        if condition(client) {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(5));
+    }
+    false
+}
+
+fn start(
+    server_plugin: DiscoveryPlugin<Lobby>,
+    client_plugin: DiscoveryPlugin<Lobby>,
+) -> (App, App) {
+    let mut server = discovery_app(server_plugin);
+    server.insert_resource(Advertisement(lobby(1)));
+    let mut client = discovery_app(client_plugin);
+    client.insert_resource(DiscoverServers::<Lobby>::default());
+    (server, client)
+}
+
+#[test]
+fn finds_server_with_its_payload() {
+    let port = free_port();
+    let (mut server, mut client) = start(plugin("TEST", port), plugin("TEST", port));
+
+    assert!(run_until(&mut server, &mut client, |client| {
+        loopback_server(client).is_some()
+    }));
+    let found = loopback_server(&client).unwrap();
+    assert_eq!(found.payload, lobby(1));
+    assert_eq!(found.addr.port(), port);
+    assert!(found.ping < Duration::from_secs(1));
+}
+
+#[test]
+fn sees_advertisement_changes() {
+    let port = free_port();
+    let (mut server, mut client) = start(plugin("TEST", port), plugin("TEST", port));
+    assert!(run_until(&mut server, &mut client, |client| {
+        loopback_server(client).is_some()
+    }));
+
+    server.world.resource_mut::<Advertisement<Lobby>>().players = 2;
+    assert!(run_until(&mut server, &mut client, |client| {
+        loopback_server(client).is_some_and(|server| server.payload == lobby(2))
+    }));
+}
+
+#[test]
+fn loses_server_that_stops_answering() {
+    let port = free_port();
+    let (mut server, mut client) = start(plugin("TEST", port), plugin("TEST", port));
+    assert!(run_until(&mut server, &mut client, |client| {
+        loopback_server(client).is_some()
+    }));
+
+    server.world.remove_resource::<Advertisement<Lobby>>();
+    assert!(run_until(&mut server, &mut client, |client| {
+        loopback_server(client).is_none()
+    }));
+    let lost = &client.world.resource::<LostServers>().0;
+    assert!(lost.iter().any(|addr| addr.ip() == Ipv4Addr::LOCALHOST));
+}
+
+#[test]
+fn ignores_other_apps() {
+    let port = free_port();
+    let (mut server, mut client) = start(plugin("OTHER", port), plugin("TEST", port));
+
+    assert!(!run_until(&mut server, &mut client, |client| {
+        loopback_server(client).is_some()
+    }));
+}
+
+#[test]
+fn ignores_other_versions() {
+    let port = free_port();
+    let (mut server, mut client) = start(plugin("TEST", port).version(2), plugin("TEST", port));
+
+    assert!(!run_until(&mut server, &mut client, |client| {
+        loopback_server(client).is_some()
+    }));
+}
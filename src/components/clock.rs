@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Time left to a seated player, owned by the server and replicated to the clients.
+///
+/// Only present on the players of timed matches.
+#[derive(Clone, Component, Copy, Debug, Deserialize, Serialize)]
+pub struct Clock {
+    remaining: Duration,
+}
+
+impl Clock {
+    pub fn new(remaining: Duration) -> Self {
+        Self { remaining }
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.remaining
+    }
+
+    pub fn is_flagged(&self) -> bool {
+        self.remaining.is_zero()
+    }
+
+    pub fn tick(&mut self, delta: Duration) {
+        self.remaining = self.remaining.saturating_sub(delta);
+    }
+
+    pub fn add(&mut self, increment: Duration) {
+        self.remaining += increment;
+    }
+}
@@ -0,0 +1,108 @@
+use bevy::prelude::*;
+use bevy_replicon::renet::ClientId;
+use serde::{Deserialize, Serialize};
+
+use super::Symbol;
+use crate::GRID_SIZE;
+
+/// Outcome of a match.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum MatchStatus {
+    #[default]
+    InProgress,
+    Won {
+        winner: Option<ClientId>,
+        symbol: Symbol,
+    },
+    Draw,
+}
+
+/// Server-authoritative state of the match, replicated to the clients that only render it.
+#[derive(Clone, Component, Debug, Default, Deserialize, Serialize)]
+pub struct MatchState {
+    current_player: Symbol,
+    move_number: usize,
+    status: MatchStatus,
+    winning_line: Option<[usize; GRID_SIZE]>,
+    /// Symbol of the player waiting for the opponent to accept a takeback.
+    takeback_request: Option<Symbol>,
+    /// Symbol of the player waiting for the opponent to accept a rematch.
+    rematch_offer: Option<Symbol>,
+}
+
+impl MatchState {
+    /// Symbol that plays the next move.
+    pub fn current_player(&self) -> Symbol {
+        self.current_player
+    }
+
+    /// Number of moves played so far.
+    pub fn move_number(&self) -> usize {
+        self.move_number
+    }
+
+    pub fn status(&self) -> MatchStatus {
+        self.status
+    }
+
+    /// Cells that completed the winning line, if the match was won on the board.
+    pub fn winning_line(&self) -> Option<[usize; GRID_SIZE]> {
+        self.winning_line
+    }
+
+    /// Symbol that played the last move, `None` before the first move.
+    pub fn last_player(&self) -> Option<Symbol> {
+        (self.move_number > 0).then(|| self.current_player.next())
+    }
+
+    pub fn takeback_request(&self) -> Option<Symbol> {
+        self.takeback_request
+    }
+
+    pub fn rematch_offer(&self) -> Option<Symbol> {
+        self.rematch_offer
+    }
+
+    /// Records that `moves` moves were played, [`Symbol::Cross`] always starts.
+    ///
+    /// A pending takeback request is dropped.
+    pub fn set_moves(&mut self, moves: usize) {
+        self.move_number = moves;
+        self.takeback_request = None;
+        self.current_player = Symbol::of_move(moves);
+    }
+
+    pub fn set_won(
+        &mut self,
+        winner: Option<ClientId>,
+        symbol: Symbol,
+        winning_line: Option<[usize; GRID_SIZE]>,
+    ) {
+        self.status = MatchStatus::Won { winner, symbol };
+        self.winning_line = winning_line;
+    }
+
+    pub fn set_draw(&mut self) {
+        self.status = MatchStatus::Draw;
+    }
+
+    pub fn request_takeback(&mut self, symbol: Symbol) {
+        self.takeback_request = Some(symbol);
+    }
+
+    pub fn decline_takeback(&mut self) {
+        self.takeback_request = None;
+    }
+
+    pub fn offer_rematch(&mut self, symbol: Symbol) {
+        self.rematch_offer = Some(symbol);
+    }
+
+    /// Undoes the last move, the match goes on even if that move ended it.
+    pub fn take_back(&mut self) {
+        self.set_moves(self.move_number.saturating_sub(1));
+        self.status = MatchStatus::InProgress;
+        self.winning_line = None;
+        self.rematch_offer = None;
+    }
+}
@@ -1,7 +1,15 @@
 mod cell_index;
+mod clock;
+mod match_state;
 mod player;
+mod rating;
+mod series;
 mod symbol;
 
 pub use cell_index::*;
+pub use clock::*;
+pub use match_state::*;
 pub use player::*;
+pub use rating::*;
+pub use series::*;
 pub use symbol::*;
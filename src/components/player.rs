@@ -4,26 +4,37 @@ use bevy_replicon::prelude::*;
 use bevy_replicon::renet::ClientId;
 use serde::{Deserialize, Serialize};
 
-/// Contains player ID and it's playing symbol.
+/// Contains player ID, it's display name and it's playing symbol.
 #[derive(Bundle)]
 pub struct PlayerBundle {
     player: Player,
+    name: PlayerName,
+    avatar_color: AvatarColor,
     symbol: Symbol,
+    score: Score,
     replication: Replication,
 }
 
 impl PlayerBundle {
-    pub fn new(client_id: ClientId, symbol: Symbol) -> Self {
+    pub fn new(
+        client_id: ClientId,
+        symbol: Symbol,
+        name: impl Into<String>,
+        avatar_color: AvatarColor,
+    ) -> Self {
         Self {
             player: Player(client_id),
+            name: PlayerName(name.into()),
+            avatar_color,
             symbol,
+            score: Score::default(),
             replication: Replication,
         }
     }
 
     /// Same as [`Self::new`], but with [`SERVER_ID`].
-    pub fn server(symbol: Symbol) -> Self {
-        Self::new(SERVER_ID, symbol)
+    pub fn server(symbol: Symbol, name: impl Into<String>, avatar_color: AvatarColor) -> Self {
+        Self::new(SERVER_ID, symbol, name, avatar_color)
     }
 }
 
@@ -34,4 +45,121 @@ impl Player {
     pub fn client_id(&self) -> ClientId {
         self.0
     }
-}
\ No newline at end of file
+
+    /// Moves the seat to another client, used when a dropped player reconnects.
+    pub fn set_client_id(&mut self, client_id: ClientId) {
+        self.0 = client_id;
+    }
+}
+
+/// Marks a seated player whose client dropped and is expected to reconnect.
+///
+/// The match is paused while any player is away.
+#[derive(Component, Serialize, Deserialize)]
+pub struct Away;
+
+/// The name displayed for a seated player or a spectator.
+#[derive(Component, Serialize, Deserialize, Deref)]
+pub struct PlayerName(String);
+
+impl PlayerName {
+    pub fn name(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Colour chosen by a player in its profile to stand out in the UI.
+#[derive(Clone, Component, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum AvatarColor {
+    #[default]
+    Red,
+    Orange,
+    Green,
+    Teal,
+    Blue,
+    Purple,
+}
+
+impl AvatarColor {
+    pub const ALL: [AvatarColor; 6] = [
+        AvatarColor::Red,
+        AvatarColor::Orange,
+        AvatarColor::Green,
+        AvatarColor::Teal,
+        AvatarColor::Blue,
+        AvatarColor::Purple,
+    ];
+
+    pub fn color(self) -> Color {
+        match self {
+            AvatarColor::Red => Color::rgb(0.85, 0.25, 0.25),
+            AvatarColor::Orange => Color::rgb(0.9, 0.55, 0.1),
+            AvatarColor::Green => Color::rgb(0.25, 0.65, 0.25),
+            AvatarColor::Teal => Color::rgb(0.1, 0.6, 0.6),
+            AvatarColor::Blue => Color::rgb(0.25, 0.4, 0.85),
+            AvatarColor::Purple => Color::rgb(0.6, 0.3, 0.75),
+        }
+    }
+
+    /// Another colour of the palette, for a second player sharing the profile.
+    pub fn next(self) -> Self {
+        let index = Self::ALL
+            .iter()
+            .position(|&color| color == self)
+            .unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+}
+
+/// Matches won by a seated player, kept across rematches.
+#[derive(Component, Default, Serialize, Deserialize)]
+pub struct Score(u32);
+
+impl Score {
+    pub fn new(wins: u32) -> Self {
+        Self(wins)
+    }
+
+    pub fn wins(&self) -> u32 {
+        self.0
+    }
+
+    pub fn add_win(&mut self) {
+        self.0 += 1;
+    }
+
+    pub fn remove_win(&mut self) {
+        self.0 = self.0.saturating_sub(1);
+    }
+
+    pub fn reset(&mut self) {
+        self.0 = 0;
+    }
+}
+
+/// Contains the ID and the name of a client that watches the match without being seated.
+#[derive(Bundle)]
+pub struct SpectatorBundle {
+    spectator: Spectator,
+    name: PlayerName,
+    replication: Replication,
+}
+
+impl SpectatorBundle {
+    pub fn new(client_id: ClientId, name: impl Into<String>) -> Self {
+        Self {
+            spectator: Spectator(client_id),
+            name: PlayerName(name.into()),
+            replication: Replication,
+        }
+    }
+}
+
+#[derive(Component, Serialize, Deserialize, Deref)]
+pub struct Spectator(ClientId);
+
+impl Spectator {
+    pub fn client_id(&self) -> ClientId {
+        self.0
+    }
+}
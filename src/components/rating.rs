@@ -0,0 +1,38 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Elo rating of a new profile.
+pub const DEFAULT_RATING: i32 = 1200;
+/// Most points a single match can move a rating.
+const K_FACTOR: f32 = 32.0;
+
+/// Elo rating of a seated player in a hosted match, updated by the server once the match ends.
+#[derive(Clone, Component, Copy, Debug, Deref, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Rating(i32);
+
+impl Rating {
+    pub fn new(rating: i32) -> Self {
+        Self(rating)
+    }
+
+    pub fn value(&self) -> i32 {
+        self.0
+    }
+
+    /// Score expected against `opponent`, between 0 and 1.
+    pub fn expected_score(&self, opponent: Rating) -> f32 {
+        1.0 / (1.0 + 10f32.powf((opponent.0 - self.0) as f32 / 400.0))
+    }
+
+    /// The rating after scoring `score` against `opponent`: 1 for a win, 0.5 for a draw and 0 for a loss.
+    pub fn rated(&self, opponent: Rating, score: f32) -> Rating {
+        let change = K_FACTOR * (score - self.expected_score(opponent));
+        Self(self.0 + change.round() as i32)
+    }
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self(DEFAULT_RATING)
+    }
+}
@@ -0,0 +1,39 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A best-of-N series played by the same players, owned by the server and replicated to the clients.
+///
+/// The wins are counted in the [`Score`](super::Score) of the players.
+#[derive(Clone, Component, Copy, Debug, Deserialize, Serialize)]
+pub struct Series {
+    best_of: u32,
+    game: u32,
+}
+
+impl Series {
+    pub fn new(best_of: u32) -> Self {
+        Self { best_of, game: 1 }
+    }
+
+    pub fn best_of(&self) -> u32 {
+        self.best_of
+    }
+
+    /// Number of the game being played, starting at 1.
+    pub fn game(&self) -> u32 {
+        self.game
+    }
+
+    /// Whether a player with `wins` took the series.
+    pub fn is_won_with(&self, wins: u32) -> bool {
+        wins > self.best_of / 2
+    }
+
+    pub fn next_game(&mut self) {
+        self.game += 1;
+    }
+
+    pub fn restart(&mut self) {
+        self.game = 1;
+    }
+}
@@ -6,7 +6,7 @@ use std::fmt::{self, Formatter};
 use super::CellIndex;
 
 /// A component that defines the symbol of a player or a filled cell.
-#[derive(Clone, Component, Copy, Default, Deserialize, Eq, PartialEq, Serialize)]
+#[derive(Clone, Component, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
 pub enum Symbol {
     #[default]
     Cross,
@@ -28,6 +28,15 @@ impl Symbol {
         }
     }
 
+    /// Symbol playing the move at `index`, counted from 0, [`Symbol::Cross`] always starts.
+    pub fn of_move(index: usize) -> Self {
+        if index.is_multiple_of(2) {
+            Symbol::Cross
+        } else {
+            Symbol::Nought
+        }
+    }
+
     pub fn next(self) -> Self {
         match self {
             Symbol::Cross => Symbol::Nought,
@@ -0,0 +1,146 @@
+use std::{fs, io, path::Path, time::Duration};
+
+use bevy::{asset::ron, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::PORT;
+
+/// File the configuration is read from, relative to the working directory.
+const CONFIG_FILE: &str = "config.ron";
+
+/// Local settings of the game, loaded from [`CONFIG_FILE`] at startup.
+///
+/// A missing file results in the default configuration.
+#[derive(Resource, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    pub network: NetworkConfig,
+    /// Rules of the matches hosted or played in hotseat.
+    pub match_settings: MatchSettings,
+}
+
+/// Room hosted and requested when none is configured.
+pub const DEFAULT_ROOM: &str = "default";
+
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Hex encoded key shared by the hosts and the clients.
+    ///
+    /// When set, connections are authenticated with connect tokens signed with this key.
+    pub private_key: Option<String>,
+    /// Room hosted by the server or requested by the client.
+    pub room: String,
+    /// Port the host listens on for players.
+    pub port: u16,
+    /// Whether the host records its matches to the `games` directory.
+    pub record_matches: bool,
+}
+
+impl Default for NetworkConfig {
+    fn default() -> Self {
+        Self {
+            private_key: None,
+            room: DEFAULT_ROOM.to_string(),
+            port: PORT,
+            record_matches: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct MatchSettings {
+    pub time_control: TimeControl,
+    pub on_timeout: TimeoutAction,
+    /// Games of a series, like 3, 5 or 7. With 1 every rematch adds to a running score.
+    pub best_of: u32,
+}
+
+impl Default for MatchSettings {
+    fn default() -> Self {
+        Self {
+            time_control: TimeControl::default(),
+            on_timeout: TimeoutAction::default(),
+            best_of: 1,
+        }
+    }
+}
+
+/// Time each player has for its moves.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum TimeControl {
+    #[default]
+    Unlimited,
+    /// The whole match has to be played in `seconds`.
+    SuddenDeath { seconds: u32 },
+    /// Starts with `seconds` and gains `increment` seconds after each move.
+    Increment { seconds: u32, increment: u32 },
+}
+
+impl TimeControl {
+    /// Time on the clocks when the match starts, `None` if the match isn't timed.
+    pub fn base(self) -> Option<Duration> {
+        match self {
+            TimeControl::Unlimited => None,
+            TimeControl::SuddenDeath { seconds } | TimeControl::Increment { seconds, .. } => {
+                Some(Duration::from_secs(seconds.into()))
+            }
+        }
+    }
+
+    /// Time added to the clock of a player after its move.
+    pub fn increment(self) -> Duration {
+        match self {
+            TimeControl::Increment { increment, .. } => Duration::from_secs(increment.into()),
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+/// What happens when a player runs out of time.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum TimeoutAction {
+    /// The opponent wins the match.
+    #[default]
+    Forfeit,
+    /// A random free cell is picked for the player.
+    RandomMove,
+}
+
+impl Config {
+    /// Loads the configuration, falling back to the default one if the file is invalid.
+    pub fn load_or_default() -> Self {
+        Self::load_from(Path::new(CONFIG_FILE))
+            .inspect_err(|e| error!("Failed to load {CONFIG_FILE}: {e}"))
+            .unwrap_or_default()
+    }
+
+    fn load_from(path: &Path) -> anyhow::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(ron::from_str(&text)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl NetworkConfig {
+    /// Decodes the private key, `None` means unsecure connections.
+    pub fn private_key<const N: usize>(&self) -> anyhow::Result<Option<[u8; N]>> {
+        let Some(hex) = &self.private_key else {
+            return Ok(None);
+        };
+        anyhow::ensure!(
+            hex.len() == N * 2,
+            "private key should be {} hex characters",
+            N * 2
+        );
+
+        let mut key = [0; N];
+        for (byte, chunk) in key.iter_mut().zip(hex.as_bytes().chunks(2)) {
+            *byte = u8::from_str_radix(std::str::from_utf8(chunk)?, 16)?;
+        }
+        Ok(Some(key))
+    }
+}
@@ -1,5 +1,7 @@
 use bevy::prelude::*;
+use bevy_replicon::renet::ClientId;
 use serde::{Deserialize, Serialize};
+use std::fmt::{self, Formatter};
 
 /// An event that indicates a symbol pick.
 ///
@@ -17,3 +19,154 @@ impl CellPick {
         self.0
     }
 }
+
+/// A [`CellPick`] the server played on the board.
+///
+/// Local to the server, it's not sent to the clients.
+#[derive(Clone, Copy, Debug, Event)]
+pub struct PickAccepted {
+    pub client_id: ClientId,
+    pub index: usize,
+}
+
+/// A [`CellPick`] the server ignored.
+///
+/// Local to the server, it's not sent to the clients.
+#[derive(Clone, Copy, Debug, Event)]
+pub struct PickRejected {
+    pub client_id: ClientId,
+    pub index: usize,
+    pub reason: PickRejection,
+}
+
+/// Tells the client why the server ignored its [`CellPick`].
+#[derive(Clone, Copy, Debug, Deserialize, Event, Serialize)]
+pub struct MoveRejected {
+    reason: PickRejection,
+}
+
+impl MoveRejected {
+    pub fn new(reason: PickRejection) -> Self {
+        Self { reason }
+    }
+
+    pub fn reason(&self) -> PickRejection {
+        self.reason
+    }
+}
+
+/// Why a [`CellPick`] was ignored.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum PickRejection {
+    OutOfRange,
+    Spectator,
+    Paused,
+    WrongTurn,
+    Occupied,
+    GameFinished,
+}
+
+impl fmt::Display for PickRejection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            PickRejection::OutOfRange => f.write_str("The cell doesn't exist."),
+            PickRejection::Spectator => f.write_str("Spectators can't play."),
+            PickRejection::Paused => f.write_str("The match is paused."),
+            PickRejection::WrongTurn => f.write_str("It's not your turn."),
+            PickRejection::Occupied => f.write_str("The cell is already taken."),
+            PickRejection::GameFinished => f.write_str("The match is over."),
+        }
+    }
+}
+
+/// A takeback of the last move, requested by the player who made it and answered by the opponent.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Event, PartialEq, Serialize)]
+pub enum Takeback {
+    Request,
+    Accept,
+    Decline,
+}
+
+/// Asks for a new match with the same players once the match is over.
+///
+/// The rematch starts when both players asked for it.
+#[derive(Clone, Copy, Debug, Deserialize, Event, Serialize)]
+pub struct RematchRequest;
+
+/// A chat message typed by a player or a spectator, relayed by the server.
+#[derive(Clone, Debug, Deserialize, Event, Serialize)]
+pub struct ChatMessage(String);
+
+impl ChatMessage {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self(text.into())
+    }
+
+    pub fn text(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A chat message relayed by the server to every client.
+#[derive(Clone, Debug, Deserialize, Event, Serialize)]
+pub struct ChatReceived {
+    /// Name of the sender, `None` for notices of the server.
+    sender: Option<String>,
+    text: String,
+}
+
+impl ChatReceived {
+    pub fn new(sender: impl Into<String>, text: impl Into<String>) -> Self {
+        Self {
+            sender: Some(sender.into()),
+            text: text.into(),
+        }
+    }
+
+    /// A message of the server itself.
+    pub fn notice(text: impl Into<String>) -> Self {
+        Self {
+            sender: None,
+            text: text.into(),
+        }
+    }
+
+    pub fn sender(&self) -> Option<&str> {
+        self.sender.as_deref()
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+}
+
+/// An event sent by the server to a client right before disconnecting it.
+///
+/// Renet doesn't allow custom disconnect reasons, so the reason is sent as an event.
+#[derive(Clone, Copy, Debug, Deserialize, Event, Serialize)]
+pub struct ConnectionRejected(RejectReason);
+
+impl ConnectionRejected {
+    pub fn new(reason: RejectReason) -> Self {
+        Self(reason)
+    }
+
+    pub fn reason(&self) -> RejectReason {
+        self.0
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum RejectReason {
+    WrongPassword,
+    WrongRoom,
+}
+
+impl fmt::Display for RejectReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RejectReason::WrongPassword => f.write_str("The password is wrong."),
+            RejectReason::WrongRoom => f.write_str("The server hosts a different room."),
+        }
+    }
+}
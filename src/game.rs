@@ -3,11 +3,21 @@ use bevy_replicon::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    components::{CellIndex, Player, Symbol, SymbolBundle},
-    events::CellPick,
+    components::{
+        Away, CellIndex, Clock, MatchState, MatchStatus, Player, Rating, Score, Series, Spectator,
+        Symbol, SymbolBundle,
+    },
+    config::{Config, TimeoutAction},
+    events::{
+        CellPick, MoveRejected, PickAccepted, PickRejected, PickRejection, RematchRequest, Takeback,
+    },
     resources::{CurrentTurn, SymbolFont, Winner},
+    save::ResumedMove,
     state::GameState,
-    utils::{any_component_added, local_player_turn, tear_down_with_component},
+    utils::{
+        any_component_added, any_component_changed, board_shown, local_player_turn,
+        tear_down_with_component,
+    },
     BACKGROUND_COLOR, BOARD_COLOR, BOARD_SIZE, BUTTON_MARGIN, BUTTON_SIZE, CELL_SIZE, GRID_SIZE,
     LINES_COUNT, LINE_THICKNESS,
 };
@@ -18,39 +28,135 @@ impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         {
             app.replicate::<GameElements>();
+            app.add_event::<PickAccepted>();
+            app.add_event::<PickRejected>();
 
-            app.add_systems(OnEnter(GameState::Playing), setup_game);
+            app.add_systems(
+                OnEnter(GameState::Playing),
+                (
+                    setup_game,
+                    (spawn_match_state, start_clocks, start_series).run_if(has_authority),
+                ),
+            );
             app.add_systems(
                 Update,
                 (
-                    cell_interaction_system.run_if(local_player_turn),
-                    picking_system.run_if(has_authority),
-                    symbol_init_system,
-                    turn_advance_system.run_if(any_component_added::<CellIndex>),
+                    cell_interaction_system
+                        .run_if(local_player_turn)
+                        .run_if(in_state(GameState::Playing)),
+                    // Replays rebuild the board the same way, from the symbols of the record.
+                    symbol_init_system
+                        .run_if(in_state(GameState::Playing).or_else(in_state(GameState::Replay))),
+                    (
+                        move_history_system,
+                        turn_advance_system
+                            .run_if(has_authority)
+                            .run_if(any_component_added::<CellIndex>),
+                        clock_system.run_if(has_authority),
+                    )
+                        .chain_ignore_deferred()
+                        .run_if(in_state(GameState::Playing)),
+                )
+                    .chain_ignore_deferred(),
+            );
+            // Not limited to a state, a resumed match is spawned before the game is played.
+            app.add_systems(
+                Update,
+                reset_match_records
+                    .before(move_history_system)
+                    .run_if(has_authority)
+                    .run_if(any_component_added::<MatchState>),
+            );
+            // Also answers the picks sent once the match is over.
+            app.add_systems(
+                Update,
+                picking_system
+                    .before(symbol_init_system)
+                    .run_if(has_authority)
+                    .run_if(board_shown),
+            );
+            // A takeback can bring a finished match back to play.
+            app.add_systems(
+                Update,
+                (
+                    takeback_system.run_if(has_authority),
+                    free_cell_system,
+                    (
+                        (score_system, rating_system).run_if(has_authority),
+                        match_state_system,
+                    )
+                        .run_if(any_component_changed::<MatchState>),
                 )
                     .chain_ignore_deferred()
-                    .run_if(in_state(GameState::Playing)),
+                    .after(clock_system)
+                    .run_if(board_shown),
             );
-
+            app.add_systems(OnEnter(GameState::Replay), setup_game);
             app.add_systems(
-                OnExit(GameState::Playing),
-                tear_down_with_component::<GameElements>,
+                Update,
+                rematch_system
+                    .run_if(has_authority)
+                    .run_if(in_state(GameState::GameOver).or_else(in_state(GameState::Draw))),
             );
+            app.init_resource::<MoveHistory>();
+            app.init_resource::<ScoredWinner>();
+            app.init_resource::<IncrementedMoves>();
+            app.init_resource::<RatingsBefore>();
+
+            // The board stays visible behind the game over screens.
+            for state in [
+                GameState::MainMenu,
+                GameState::Reconnecting,
+                GameState::Disconnected,
+            ] {
+                app.add_systems(OnEnter(state), tear_down_with_component::<GameElements>);
+            }
         }
     }
 }
 
 #[derive(Component, Serialize, Deserialize)]
-struct GameElements;
+pub(crate) struct GameElements;
 
 #[derive(Component)]
-struct GridNode;
+pub(crate) struct GridNode;
+
+/// Symbols in the order they were played, to take back the last move and to export the game.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub(crate) struct MoveHistory(Vec<Entity>);
+
+/// Player whose win of the current match was added to its [`Score`].
+///
+/// Only for single-player and server.
+#[derive(Resource, Default, Deref, DerefMut)]
+struct ScoredWinner(Option<Entity>);
+
+/// Moves of the current match that earned the increment, a move taken back and played again doesn't earn it twice.
+///
+/// Only for single-player and server.
+#[derive(Resource, Default, Deref, DerefMut)]
+struct IncrementedMoves(usize);
+
+/// Marks the moves played for a player whose clock ran out, they don't earn the increment.
+#[derive(Component)]
+struct TimeoutMove;
+
+/// Ratings of the players when the current match started, the result is always applied to them.
+///
+/// Only for server.
+#[derive(Resource, Default, Deref, DerefMut)]
+struct RatingsBefore(Vec<(Entity, Rating)>);
 
 fn setup_game(
     mut commands: Commands,
     mut winner: ResMut<Winner>,
     mut current_turn: ResMut<CurrentTurn>,
+    grid_nodes: Query<(), With<GridNode>>,
 ) {
+    // The board is still there when the end of the match was taken back.
+    if !grid_nodes.is_empty() {
+        return;
+    }
     winner.clear();
     current_turn.reset();
 
@@ -171,53 +277,118 @@ fn cell_interaction_system(
     }
 }
 
-/// Handles cell pick events.
+/// Spawns the replicated state of the match.
+///
+/// Only for single-player and server.
+fn spawn_match_state(mut commands: Commands, match_states: Query<(), With<MatchState>>) {
+    if !match_states.is_empty() {
+        return;
+    }
+    commands
+        .spawn((MatchState::default(), Replication))
+        .insert(GameElements);
+}
+
+/// Sets the clocks of the players when the match is timed.
+///
+/// Only for single-player and server.
+fn start_clocks(
+    mut commands: Commands,
+    config: Res<Config>,
+    players: Query<Entity, (With<Player>, Without<Clock>)>,
+) {
+    let Some(base) = config.match_settings.time_control.base() else {
+        return;
+    };
+    for player_entity in &players {
+        commands.entity(player_entity).insert(Clock::new(base));
+    }
+}
+
+/// Spawns the series when the players play several games in a row.
+///
+/// Only for single-player and server.
+fn start_series(mut commands: Commands, config: Res<Config>, series: Query<(), With<Series>>) {
+    let best_of = config.match_settings.best_of;
+    if best_of > 1 && series.is_empty() {
+        commands
+            .spawn((Series::new(best_of), Replication))
+            .insert(GameElements);
+    }
+}
+
+/// Handles cell pick events, the client is told why its pick was rejected.
 ///
 /// Only for single-player and server.
 fn picking_system(
     mut commands: Commands,
     mut pick_events: EventReader<FromClient<CellPick>>,
+    mut accepted_events: EventWriter<PickAccepted>,
+    mut rejected_events: EventWriter<PickRejected>,
+    mut move_rejected_events: EventWriter<ToClients<MoveRejected>>,
     symbols: Query<&CellIndex>,
-    current_turn: Res<CurrentTurn>,
+    match_state: Query<&MatchState>,
     players: Query<(&Player, &Symbol)>,
+    spectators: Query<&Spectator>,
+    away_players: Query<(), (With<Player>, With<Away>)>,
 ) {
+    let Ok(match_state) = match_state.get_single() else {
+        return;
+    };
+    let current_player = match_state.current_player();
+    // The turn advances once the symbol of the last pick is on the board, a frame later.
+    let mut move_pending = symbols.iter().len() != match_state.move_number();
+
     for FromClient { client_id, event } in pick_events.read().copied() {
+        let index = event.index();
         // It's good to check the received data, client could be cheating.
-        if event.index() > GRID_SIZE * GRID_SIZE {
-            debug!("received invalid cell index {:?}", event.index());
-            continue;
-        }
-
-        if !players.iter().any(|(player, &symbol)| {
-            player.client_id() == client_id && symbol == current_turn.symbol()
-        }) {
-            debug!(
-                "player {client_id} chose cell {:?} at wrong turn",
-                event.index()
-            );
-            continue;
-        }
-
-        if symbols
+        let reason = if index >= GRID_SIZE * GRID_SIZE {
+            Some(PickRejection::OutOfRange)
+        } else if match_state.status() != MatchStatus::InProgress {
+            Some(PickRejection::GameFinished)
+        } else if spectators
             .iter()
-            .any(|cell_index| cell_index.index() == event.index())
+            .any(|spectator| spectator.client_id() == client_id)
         {
-            debug!(
-                "player {client_id} has chosen an already occupied cell {:?}",
-                event.index()
-            );
+            Some(PickRejection::Spectator)
+        } else if !away_players.is_empty() {
+            Some(PickRejection::Paused)
+        } else if move_pending
+            || !players.iter().any(|(player, &symbol)| {
+                player.client_id() == client_id && symbol == current_player
+            })
+        {
+            Some(PickRejection::WrongTurn)
+        } else if symbols.iter().any(|cell_index| cell_index.index() == index) {
+            Some(PickRejection::Occupied)
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            debug!("ignoring cell {index} chosen by client {client_id}: {reason:?}");
+            rejected_events.send(PickRejected {
+                client_id,
+                index,
+                reason,
+            });
+            move_rejected_events.send(ToClients {
+                mode: SendMode::Direct(client_id),
+                event: MoveRejected::new(reason),
+            });
             continue;
         }
 
         // Spawn "blueprint" of the cell that client will replicate.
         commands
-            .spawn(SymbolBundle::new(current_turn.symbol(), event.index()))
+            .spawn(SymbolBundle::new(current_player, index))
             .insert(GameElements);
+        accepted_events.send(PickAccepted { client_id, index });
+        move_pending = true;
     }
 }
 
 /// Initializes spawned symbol on client after replication and on server / single-player right after the spawn.
-fn symbol_init_system(
+pub(crate) fn symbol_init_system(
     mut commands: Commands,
     symbol_font: Res<SymbolFont>,
     symbols: Query<(Entity, &CellIndex, &Symbol), Added<Symbol>>,
@@ -253,19 +424,10 @@ fn symbol_init_system(
     }
 }
 
-/// Checks the winner and advances the turn.
-fn turn_advance_system(
-    mut commands: Commands,
-    mut current_turn: ResMut<CurrentTurn>,
-    mut game_state: ResMut<NextState<GameState>>,
-    players: Query<(&Player, &Symbol)>,
-    symbols: Query<(&CellIndex, &Symbol)>,
-) {
-    let mut board = [None; GRID_SIZE * GRID_SIZE];
-    for (cell_index, &symbol) in &symbols {
-        board[cell_index.index()] = Some(symbol);
-    }
-
+/// The symbol that completed a line of the board and that line, if any.
+pub(crate) fn winning_line(
+    board: &[Option<Symbol>; GRID_SIZE * GRID_SIZE],
+) -> Option<(Symbol, [usize; GRID_SIZE])> {
     const WIN_CONDITIONS: [[usize; GRID_SIZE]; 8] = [
         [0, 1, 2],
         [3, 4, 5],
@@ -277,23 +439,717 @@ fn turn_advance_system(
         [2, 4, 6],
     ];
 
-    for indexes in WIN_CONDITIONS {
+    WIN_CONDITIONS.into_iter().find_map(|indexes| {
         let symbols = indexes.map(|index| board[index]);
-        if symbols[0].is_some() && symbols.windows(2).all(|symbols| symbols[0] == symbols[1]) {
-            game_state.set(GameState::GameOver);
-            // Find the player with the winning symbol.
-            let winner = players
+        let symbol = symbols[0]?;
+        symbols
+            .windows(2)
+            .all(|symbols| symbols[0] == symbols[1])
+            .then_some((symbol, indexes))
+    })
+}
+
+/// Forgets what the previous match left in the per-match resources, however the new match started.
+///
+/// Only for single-player and server.
+fn reset_match_records(
+    mut history: ResMut<MoveHistory>,
+    mut scored_winner: ResMut<ScoredWinner>,
+    mut incremented_moves: ResMut<IncrementedMoves>,
+    mut ratings_before: ResMut<RatingsBefore>,
+) {
+    history.clear();
+    **scored_winner = None;
+    **incremented_moves = 0;
+    ratings_before.clear();
+}
+
+/// Keeps the [`MoveHistory`] in sync with the symbols on the board.
+///
+/// Clients also follow the moves, symbols replicated together are assumed to be in order.
+fn move_history_system(
+    mut history: ResMut<MoveHistory>,
+    cells: Query<(), With<CellIndex>>,
+    new_symbols: Query<Entity, Added<CellIndex>>,
+) {
+    history.retain(|&entity| cells.contains(entity));
+    history.extend(&new_symbols);
+}
+
+/// Checks the winner and advances the turn.
+///
+/// Only for single-player and server, clients receive the outcome through [`MatchState`].
+fn turn_advance_system(
+    mut match_state: Query<&mut MatchState>,
+    players: Query<(&Player, &Symbol)>,
+    symbols: Query<(&CellIndex, &Symbol)>,
+) {
+    let Ok(mut match_state) = match_state.get_single_mut() else {
+        return;
+    };
+
+    let mut board = [None; GRID_SIZE * GRID_SIZE];
+    for (cell_index, &symbol) in &symbols {
+        board[cell_index.index()] = Some(symbol);
+    }
+    match_state.set_moves(board.iter().flatten().count());
+
+    if let Some((symbol, line)) = winning_line(&board) {
+        // Find the player with the winning symbol.
+        let winner = players
+            .iter()
+            .find(|(_, &player_symbol)| player_symbol == symbol)
+            .map(|(player, _)| player.client_id());
+        match_state.set_won(winner, symbol, Some(line));
+        return;
+    }
+
+    if board.iter().all(Option::is_some) {
+        match_state.set_draw();
+    }
+}
+
+/// Runs the clock of the player to move and applies the increment after each move.
+///
+/// Resumed moves, moves played again after a takeback and random moves of a flagged player don't earn it.
+/// A player whose clock runs out forfeits or gets a random move, as configured.
+/// Once flagged, every following move of that player is handled the same way.
+/// Clocks are paused while a player is away.
+///
+/// Only for single-player and server.
+fn clock_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<Config>,
+    history: Res<MoveHistory>,
+    mut incremented_moves: ResMut<IncrementedMoves>,
+    mut match_state: Query<&mut MatchState>,
+    mut clocks: Query<(&Player, &Symbol, &mut Clock)>,
+    away_players: Query<(), (With<Player>, With<Away>)>,
+    new_symbols: Query<(Entity, &Symbol, Has<ResumedMove>, Has<TimeoutMove>), Added<CellIndex>>,
+    cells: Query<&CellIndex>,
+) {
+    let Ok(mut match_state) = match_state.get_single_mut() else {
+        return;
+    };
+    if match_state.status() != MatchStatus::InProgress {
+        return;
+    }
+
+    let settings = config.match_settings;
+    for (symbol_entity, &symbol, resumed, timeout) in &new_symbols {
+        let Some(moves) = history
+            .iter()
+            .position(|&entity| entity == symbol_entity)
+            .map(|index| index + 1)
+        else {
+            continue;
+        };
+        if moves <= **incremented_moves {
+            continue;
+        }
+        **incremented_moves = moves;
+        if resumed || timeout {
+            continue;
+        }
+        for (.., mut clock) in clocks.iter_mut().filter(|(_, &s, _)| s == symbol) {
+            clock.add(settings.time_control.increment());
+        }
+    }
+
+    if !away_players.is_empty() {
+        return;
+    }
+
+    let current_player = match_state.current_player();
+    let Some((player, _, mut clock)) = clocks
+        .iter_mut()
+        .find(|(_, &symbol, _)| symbol == current_player)
+    else {
+        return;
+    };
+    let already_flagged = clock.is_flagged();
+    if !already_flagged {
+        clock.tick(time.delta());
+    }
+    // A flagged player is handled once per turn, when the turn starts.
+    if !clock.is_flagged() || (already_flagged && new_symbols.is_empty()) {
+        return;
+    }
+
+    match settings.on_timeout {
+        TimeoutAction::Forfeit => {
+            info!("player {} ran out of time", player.client_id());
+            if let Some((opponent, &symbol, _)) = clocks
                 .iter()
-                .find(|(_, &symbol)| symbol == symbols[0].unwrap())
-                .map(|(player, _)| player.client_id());
-            commands.insert_resource(Winner::new(winner));
+                .find(|(_, &symbol, _)| symbol != current_player)
+            {
+                match_state.set_won(Some(opponent.client_id()), symbol, None);
+            }
+        }
+        TimeoutAction::RandomMove => {
+            let mut free = [true; GRID_SIZE * GRID_SIZE];
+            for cell_index in &cells {
+                free[cell_index.index()] = false;
+            }
+            let free_cells: Vec<_> = (0..free.len()).filter(|&index| free[index]).collect();
+            if let Some(index) = fastrand::choice(free_cells) {
+                info!(
+                    "player {} ran out of time, playing cell {index}",
+                    player.client_id()
+                );
+                commands
+                    .spawn(SymbolBundle::new(current_player, index))
+                    .insert((GameElements, TimeoutMove));
+            }
+        }
+    }
+}
+
+/// Handles takeback requests and answers.
+///
+/// Players can only take back their own move. In hotseat it's applied right away,
+/// online the opponent has to accept it.
+///
+/// Only for single-player and server.
+fn takeback_system(
+    mut commands: Commands,
+    mut takeback_events: EventReader<FromClient<Takeback>>,
+    mut history: ResMut<MoveHistory>,
+    mut match_state: Query<&mut MatchState>,
+    players: Query<(&Player, &Symbol)>,
+    away_players: Query<(), (With<Player>, With<Away>)>,
+) {
+    let Ok(mut match_state) = match_state.get_single_mut() else {
+        return;
+    };
+
+    for FromClient { client_id, event } in takeback_events.read().copied() {
+        let plays = |symbol: Symbol| {
+            players
+                .iter()
+                .any(|(player, &s)| player.client_id() == client_id && s == symbol)
+        };
+        let Some(last_player) = match_state.last_player() else {
+            debug!("client {client_id} sent {event:?} before the first move");
+            continue;
+        };
+        // Forfeits and timeouts are final, only moves can be taken back.
+        if matches!(match_state.status(), MatchStatus::Won { .. })
+            && match_state.winning_line().is_none()
+        {
+            debug!("client {client_id} sent {event:?} after a forfeit");
+            continue;
+        }
+
+        let accepted = match event {
+            Takeback::Request => {
+                if !plays(last_player) {
+                    debug!("client {client_id} requested a takeback of a move it didn't play");
+                    continue;
+                }
+                if !away_players.is_empty() {
+                    debug!("client {client_id} requested a takeback while the match is paused");
+                    continue;
+                }
+                // In hotseat the opponent is at the same screen.
+                plays(last_player.next())
+            }
+            Takeback::Accept | Takeback::Decline => {
+                if match_state.takeback_request() != Some(last_player) || !plays(last_player.next())
+                {
+                    debug!("client {client_id} answered a takeback that wasn't requested");
+                    continue;
+                }
+                event == Takeback::Accept
+            }
+        };
+
+        if accepted {
+            info!("taking back the last move of {last_player}");
+            if let Some(symbol_entity) = history.pop() {
+                commands.entity(symbol_entity).despawn_recursive();
+            }
+            match_state.take_back();
+        } else if event == Takeback::Request {
+            match_state.request_takeback(last_player);
+        } else {
+            info!("takeback of {last_player} declined");
+            match_state.decline_takeback();
+        }
+    }
+}
+
+/// Makes the cells freed by a takeback clickable again.
+fn free_cell_system(
+    mut commands: Commands,
+    mut removed_cells: RemovedComponents<CellIndex>,
+    grid_nodes: Query<&Children, With<GridNode>>,
+    mut buttons: Query<
+        (Option<&Children>, &mut BackgroundColor),
+        (With<Button>, Without<Interaction>),
+    >,
+) {
+    if removed_cells.read().count() == 0 {
+        return;
+    }
+    let Ok(grid_buttons) = grid_nodes.get_single() else {
+        return;
+    };
+    for &button_entity in grid_buttons {
+        let Ok((children, mut background)) = buttons.get_mut(button_entity) else {
+            continue;
+        };
+        // Despawned symbols leave an empty list of children behind.
+        if children.is_none_or(|children| children.is_empty()) {
+            // It may have been highlighted in the winning line.
+            *background = BACKGROUND_COLOR.into();
+            commands
+                .entity(button_entity)
+                .insert(Interaction::default());
+        }
+    }
+}
+
+/// Keeps the [`Score`] of the players in sync with the outcome of the match, that a takeback may undo.
+///
+/// Only for single-player and server.
+fn score_system(
+    mut scored_winner: ResMut<ScoredWinner>,
+    match_state: Query<&MatchState>,
+    mut players: Query<(Entity, &Symbol, &mut Score), With<Player>>,
+) {
+    let Ok(match_state) = match_state.get_single() else {
+        return;
+    };
+    let winner = match match_state.status() {
+        MatchStatus::Won { symbol, .. } => players
+            .iter()
+            .find(|(_, &player_symbol, _)| player_symbol == symbol)
+            .map(|(player_entity, ..)| player_entity),
+        MatchStatus::InProgress | MatchStatus::Draw => None,
+    };
+    if winner == **scored_winner {
+        return;
+    }
+
+    if let Some(Ok((.., mut score))) = scored_winner.map(|entity| players.get_mut(entity)) {
+        score.remove_win();
+    }
+    if let Some(Ok((.., mut score))) = winner.map(|entity| players.get_mut(entity)) {
+        score.add_win();
+    }
+    **scored_winner = winner;
+}
+
+/// Rates the players of a hosted match from its outcome, restoring their ratings if it's taken back.
+///
+/// Hotseat players have no [`Rating`].
+///
+/// Only for server.
+fn rating_system(
+    mut ratings_before: ResMut<RatingsBefore>,
+    match_state: Query<&MatchState>,
+    mut players: Query<(Entity, &Symbol, &mut Rating), With<Player>>,
+) {
+    let Ok(match_state) = match_state.get_single() else {
+        return;
+    };
+    if ratings_before.is_empty() {
+        let ratings: Vec<_> = players
+            .iter()
+            .map(|(player_entity, _, &rating)| (player_entity, rating))
+            .collect();
+        if ratings.len() != 2 {
             return;
         }
+        **ratings_before = ratings;
     }
 
-    if board.iter().all(Option::is_some) {
-        game_state.set(GameState::Draw);
-    } else {
-        current_turn.next();
+    for &(player_entity, before) in ratings_before.iter() {
+        let Some(&(_, opponent)) = ratings_before
+            .iter()
+            .find(|&&(entity, _)| entity != player_entity)
+        else {
+            continue;
+        };
+        let Ok((_, &symbol, mut rating)) = players.get_mut(player_entity) else {
+            continue;
+        };
+        let new_rating = match match_state.status() {
+            MatchStatus::InProgress => before,
+            MatchStatus::Won { symbol: winner, .. } if winner == symbol => {
+                before.rated(opponent, 1.0)
+            }
+            MatchStatus::Won { .. } => before.rated(opponent, 0.0),
+            MatchStatus::Draw => before.rated(opponent, 0.5),
+        };
+        if *rating != new_rating {
+            info!("rating of {player_entity:?} is now {}", new_rating.value());
+            *rating = new_rating;
+        }
+    }
+}
+
+/// Starts a new match with the same players once both asked for it.
+///
+/// The board is cleared and the players swap their symbols, so the other one starts.
+/// Once a series is won, the rematch starts a new series.
+/// In hotseat a single request is enough.
+///
+/// Only for single-player and server.
+fn rematch_system(
+    mut commands: Commands,
+    mut rematch_events: EventReader<FromClient<RematchRequest>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut match_state: Query<(Entity, &mut MatchState)>,
+    mut players: Query<(Entity, &Player, &mut Symbol, &mut Score)>,
+    mut series: Query<&mut Series>,
+    away_players: Query<(), (With<Player>, With<Away>)>,
+    cells: Query<Entity, With<CellIndex>>,
+) {
+    let Ok((match_state_entity, mut match_state)) = match_state.get_single_mut() else {
+        return;
+    };
+
+    for FromClient { client_id, .. } in rematch_events.read().copied() {
+        let symbols: Vec<_> = players
+            .iter()
+            .filter(|(_, player, ..)| player.client_id() == client_id)
+            .map(|(_, _, &symbol, _)| symbol)
+            .collect();
+        let Some(&symbol) = symbols.first() else {
+            debug!("client {client_id} asked for a rematch without being seated");
+            continue;
+        };
+        if !away_players.is_empty() {
+            debug!("client {client_id} asked for a rematch while a player is away");
+            continue;
+        }
+
+        // In hotseat both players are at the same screen.
+        let accepted = symbols.len() > 1
+            || match_state
+                .rematch_offer()
+                .is_some_and(|offer| offer != symbol);
+        if !accepted {
+            info!("client {client_id} asks for a rematch");
+            match_state.offer_rematch(symbol);
+            continue;
+        }
+
+        info!("starting a rematch");
+        for cell_entity in &cells {
+            commands.entity(cell_entity).despawn_recursive();
+        }
+        commands.entity(match_state_entity).despawn_recursive();
+        if let Ok(mut series) = series.get_single_mut() {
+            if players
+                .iter()
+                .any(|(.., score)| series.is_won_with(score.wins()))
+            {
+                info!("starting a new series");
+                series.restart();
+                for (.., mut score) in &mut players {
+                    score.reset();
+                }
+            } else {
+                series.next_game();
+            }
+        }
+        for (player_entity, _, mut symbol, _) in &mut players {
+            *symbol = symbol.next();
+            commands.entity(player_entity).remove::<Clock>();
+        }
+        game_state.set(GameState::Playing);
+        return;
+    }
+}
+
+/// Renders the replicated [`MatchState`] into the local turn, winner and game state.
+fn match_state_system(
+    mut commands: Commands,
+    mut current_turn: ResMut<CurrentTurn>,
+    state: Res<State<GameState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    match_state: Query<&MatchState>,
+    grid_nodes: Query<&Children, With<GridNode>>,
+    mut background_colors: Query<&mut BackgroundColor>,
+) {
+    let Ok(match_state) = match_state.get_single() else {
+        return;
+    };
+
+    *current_turn = CurrentTurn::new(match_state.current_player());
+    match match_state.status() {
+        MatchStatus::InProgress => {
+            // The move that ended the match was taken back.
+            if *state.get() != GameState::Playing {
+                if let Ok(buttons) = grid_nodes.get_single() {
+                    let mut background_colors = background_colors.iter_many_mut(buttons);
+                    while let Some(mut background) = background_colors.fetch_next() {
+                        *background = BACKGROUND_COLOR.into();
+                    }
+                }
+                game_state.set(GameState::Playing);
+            }
+        }
+        MatchStatus::Won { winner, symbol } => {
+            if let (Some(line), Ok(buttons)) = (match_state.winning_line(), grid_nodes.get_single())
+            {
+                for index in line {
+                    if let Ok(mut background) = background_colors.get_mut(buttons[index]) {
+                        *background = symbol.color().with_a(0.3).into();
+                    }
+                }
+            }
+            commands.insert_resource(Winner::new(winner));
+            game_state.set(GameState::GameOver);
+        }
+        MatchStatus::Draw => {
+            commands.insert_resource(Winner::new(None));
+            game_state.set(GameState::Draw);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::Events;
+    use bevy_replicon::renet::ClientId;
+
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        components::AvatarColor,
+        config::TimeControl,
+        save::{SavedGame, SavedMode, SavedPlayer},
+        testing::{board, hotseat_app, play, set_state, settle, state},
+    };
+
+    /// Picks of the hotseat player, who plays both symbols.
+    fn hotseat_picks(cells: &[usize]) -> Vec<(ClientId, usize)> {
+        cells.iter().map(|&cell| (SERVER_ID, cell)).collect()
+    }
+
+    /// Sends a single pick and returns why it was rejected.
+    fn rejection(app: &mut App, client_id: ClientId, index: usize) -> Option<PickRejection> {
+        app.world.send_event(FromClient {
+            client_id,
+            event: CellPick::new(index),
+        });
+        app.update();
+        let events = app.world.resource::<Events<PickRejected>>();
+        let reason = events
+            .iter_current_update_events()
+            .find(|event| event.client_id == client_id && event.index == index)
+            .map(|event| event.reason);
+        reason
+    }
+
+    /// Times the match, with 10 seconds of increment, and starts the clocks with `seconds`.
+    fn set_clocks(app: &mut App, seconds: u32, on_timeout: TimeoutAction) {
+        let mut config = app.world.resource_mut::<Config>();
+        config.match_settings.time_control = TimeControl::Increment {
+            seconds,
+            increment: 10,
+        };
+        config.match_settings.on_timeout = on_timeout;
+        let players: Vec<_> = app
+            .world
+            .query_filtered::<Entity, With<Player>>()
+            .iter(&app.world)
+            .collect();
+        for player_entity in players {
+            app.world
+                .entity_mut(player_entity)
+                .insert(Clock::new(Duration::from_secs(seconds.into())));
+        }
+    }
+
+    fn remaining(app: &mut App, symbol: Symbol) -> Duration {
+        app.world
+            .query::<(&Symbol, &Clock)>()
+            .iter(&app.world)
+            .find(|(&s, _)| s == symbol)
+            .map(|(_, clock)| clock.remaining())
+            .unwrap()
+    }
+
+    #[test]
+    fn row_wins() {
+        let mut app = hotseat_app();
+        play(&mut app, &hotseat_picks(&[0, 3, 1, 4, 2]));
+
+        assert_eq!(state(&app), GameState::GameOver);
+        assert_eq!(app.world.resource::<Winner>().client_id(), Some(SERVER_ID));
+        let board = board(&mut app);
+        assert_eq!(board[..3], [Some(Symbol::Cross); 3]);
+        assert_eq!(board[3..5], [Some(Symbol::Nought); 2]);
+        assert_eq!(board.iter().flatten().count(), 5);
+    }
+
+    #[test]
+    fn full_board_draws() {
+        let mut app = hotseat_app();
+        play(&mut app, &hotseat_picks(&[0, 1, 2, 4, 3, 5, 7, 6, 8]));
+
+        assert_eq!(state(&app), GameState::Draw);
+        assert_eq!(app.world.resource::<Winner>().client_id(), None);
+        assert!(board(&mut app).iter().all(Option::is_some));
+    }
+
+    #[test]
+    fn invalid_picks_are_rejected() {
+        let mut app = hotseat_app();
+        play(&mut app, &hotseat_picks(&[4]));
+
+        assert_eq!(
+            rejection(&mut app, SERVER_ID, 4),
+            Some(PickRejection::Occupied)
+        );
+        for index in [9, 10] {
+            assert_eq!(
+                rejection(&mut app, SERVER_ID, index),
+                Some(PickRejection::OutOfRange)
+            );
+        }
+        assert_eq!(
+            rejection(&mut app, ClientId::from_raw(42), 0),
+            Some(PickRejection::WrongTurn)
+        );
+        assert_eq!(rejection(&mut app, SERVER_ID, 0), None);
+
+        let board = board(&mut app);
+        assert_eq!(board[4], Some(Symbol::Cross));
+        assert_eq!(board[0], Some(Symbol::Nought));
+        assert_eq!(board.iter().flatten().count(), 2);
+        assert_eq!(state(&app), GameState::Playing);
+    }
+
+    #[test]
+    fn picks_after_the_end_are_rejected() {
+        let mut app = hotseat_app();
+        play(&mut app, &hotseat_picks(&[0, 3, 1, 4, 2]));
+        assert_eq!(state(&app), GameState::GameOver);
+
+        assert_eq!(
+            rejection(&mut app, SERVER_ID, 8),
+            Some(PickRejection::GameFinished)
+        );
+        // The hotseat player is told too.
+        let events = app.world.resource::<Events<MoveRejected>>();
+        assert!(events
+            .iter_current_update_events()
+            .any(|event| event.reason() == PickRejection::GameFinished));
+        assert!(board(&mut app)[8].is_none());
+    }
+
+    #[test]
+    fn replayed_move_earns_no_increment() {
+        let mut app = hotseat_app();
+        set_clocks(&mut app, 60, TimeoutAction::Forfeit);
+        play(&mut app, &hotseat_picks(&[0]));
+        let after_move = remaining(&mut app, Symbol::Cross);
+        assert!(after_move > Duration::from_secs(60));
+
+        app.world.send_event(FromClient {
+            client_id: SERVER_ID,
+            event: Takeback::Request,
+        });
+        settle(&mut app);
+        assert!(board(&mut app).iter().all(Option::is_none));
+
+        play(&mut app, &hotseat_picks(&[0]));
+        assert!(remaining(&mut app, Symbol::Cross) < after_move);
+    }
+
+    #[test]
+    fn rematch_moves_earn_the_increment() {
+        let mut app = hotseat_app();
+        set_clocks(&mut app, 60, TimeoutAction::Forfeit);
+        play(&mut app, &hotseat_picks(&[0, 3, 1, 4, 2]));
+        assert_eq!(state(&app), GameState::GameOver);
+
+        app.world.send_event(FromClient {
+            client_id: SERVER_ID,
+            event: RematchRequest,
+        });
+        settle(&mut app);
+        assert_eq!(state(&app), GameState::Playing);
+        assert!(board(&mut app).iter().all(Option::is_none));
+
+        play(&mut app, &hotseat_picks(&[0]));
+        assert!(remaining(&mut app, Symbol::Cross) > Duration::from_secs(60));
+    }
+
+    #[test]
+    fn resumed_moves_earn_the_increment() {
+        let mut app = hotseat_app();
+        play(&mut app, &hotseat_picks(&[0, 3, 1, 4, 2]));
+        set_state(&mut app, GameState::MainMenu);
+
+        app.world
+            .resource_mut::<Config>()
+            .match_settings
+            .time_control = TimeControl::Increment {
+            seconds: 60,
+            increment: 10,
+        };
+        let player = |name: &str, symbol| SavedPlayer {
+            name: name.to_string(),
+            symbol,
+            avatar_color: AvatarColor::default(),
+            wins: 0,
+            clock: Some(Duration::from_secs(60)),
+        };
+        app.insert_resource(SavedGame {
+            mode: SavedMode::Hotseat,
+            players: vec![
+                player("Player 1", Symbol::Cross),
+                player("Player 2", Symbol::Nought),
+            ],
+            moves: vec![0],
+            series: None,
+        });
+        set_state(&mut app, GameState::Hotseat);
+        assert_eq!(state(&app), GameState::Playing);
+
+        play(&mut app, &hotseat_picks(&[4]));
+        assert_eq!(board(&mut app).iter().flatten().count(), 2);
+        assert!(remaining(&mut app, Symbol::Nought) > Duration::from_secs(60));
+    }
+
+    #[test]
+    fn timeout_move_earns_no_increment() {
+        let mut app = hotseat_app();
+        set_clocks(&mut app, 1, TimeoutAction::RandomMove);
+        for _ in 0..100 {
+            app.update();
+        }
+
+        assert_eq!(board(&mut app).iter().flatten().count(), 1);
+        assert!(remaining(&mut app, Symbol::Cross).is_zero());
+    }
+
+    #[test]
+    fn one_move_per_turn() {
+        let mut app = hotseat_app();
+        // Both picks arrive in the same update, the second one before the turn advanced.
+        app.world.send_event(FromClient {
+            client_id: SERVER_ID,
+            event: CellPick::new(0),
+        });
+        assert_eq!(
+            rejection(&mut app, SERVER_ID, 1),
+            Some(PickRejection::WrongTurn)
+        );
+        settle(&mut app);
+
+        let board = board(&mut app);
+        assert_eq!(board[0], Some(Symbol::Cross));
+        assert_eq!(board.iter().flatten().count(), 1);
+        assert_eq!(rejection(&mut app, SERVER_ID, 1), None);
     }
 }
@@ -1,9 +1,20 @@
+// Bevy systems routinely take many parameters and complex queries.
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
 mod components;
+mod config;
 mod events;
 mod game;
 mod network;
+mod notation;
+mod profile;
+mod replay;
 mod resources;
+mod save;
 mod state;
+mod stats;
+#[cfg(test)]
+mod testing;
 mod ui;
 mod utils;
 
@@ -31,11 +42,17 @@ fn main() {
     app.add_plugins(network::NetworkPlugin);
 
     app.init_state::<GameState>();
+    app.insert_resource(config::Config::load_or_default());
+    app.insert_resource(profile::Profile::load_or_default());
     app.init_resource::<ServerConnectionInfo>();
     app.init_resource::<CurrentTurn>();
     app.init_resource::<SymbolFont>();
     app.insert_resource(Winner::default());
     app.add_plugins(game::GamePlugin);
+    app.add_plugins(notation::NotationPlugin);
+    app.add_plugins(replay::ReplayPlugin);
+    app.add_plugins(save::SavePlugin);
+    app.add_plugins(stats::StatsPlugin);
     app.add_plugins(ui::MenuPlugin);
 
     app.add_systems(Startup, setup_camera);
@@ -50,6 +67,9 @@ fn setup_camera(mut commands: Commands) {
     commands.spawn(Camera2dBundle::default());
 }
 
+/// Escape leaves the current screen for the main menu.
+///
+/// Unfinished hotseat matches never see Escape here, `ui::quit` consumes it to offer to save first.
 fn return_to_main_menu(
     mut state: ResMut<NextState<GameState>>,
     keyboard_input: Res<ButtonInput<KeyCode>>,
@@ -0,0 +1,89 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy::prelude::*;
+use bevy_replicon::{prelude::*, renet::ClientId};
+
+use crate::{
+    components::{Player, PlayerName, Spectator},
+    events::{ChatMessage, ChatReceived},
+    state::GameState,
+};
+
+/// Longest chat message accepted by the server, in characters.
+pub const MAX_CHAT_CHARS: usize = 100;
+/// Number of messages a client can send in [`CHAT_RATE_WINDOW`].
+const CHAT_RATE_LIMIT: usize = 5;
+const CHAT_RATE_WINDOW: Duration = Duration::from_secs(10);
+
+pub struct ChatNetworkPlugin;
+
+impl Plugin for ChatNetworkPlugin {
+    fn build(&self, app: &mut App) {
+        {
+            app.init_resource::<ChatRateLimits>();
+            app.add_systems(
+                Update,
+                relay_chat_messages
+                    .run_if(has_authority)
+                    .run_if(on_event::<FromClient<ChatMessage>>()),
+            );
+            app.add_systems(OnEnter(GameState::MainMenu), clear_rate_limits);
+        }
+    }
+}
+
+/// When the recent messages of each client were sent.
+#[derive(Resource, Default, Deref, DerefMut)]
+struct ChatRateLimits(HashMap<ClientId, Vec<Duration>>);
+
+/// Relays the chat messages to every client with the name of the sender.
+///
+/// Empty and too long messages are dropped, and clients sending too many messages get a notice instead.
+fn relay_chat_messages(
+    time: Res<Time>,
+    mut rate_limits: ResMut<ChatRateLimits>,
+    mut chat_events: EventReader<FromClient<ChatMessage>>,
+    mut received_events: EventWriter<ToClients<ChatReceived>>,
+    names: Query<(&PlayerName, AnyOf<(&Player, &Spectator)>)>,
+) {
+    for FromClient { client_id, event } in chat_events.read() {
+        let text = event.text().trim();
+        let chars = text.chars().count();
+        if text.is_empty() || chars > MAX_CHAT_CHARS {
+            debug!("dropping chat message of {chars} characters from {client_id}");
+            continue;
+        }
+
+        let Some((name, _)) = names.iter().find(|(_, (player, spectator))| {
+            player
+                .map(Player::client_id)
+                .or(spectator.map(Spectator::client_id))
+                == Some(*client_id)
+        }) else {
+            debug!("dropping chat message from unknown client {client_id}");
+            continue;
+        };
+
+        let now = time.elapsed();
+        let sent = rate_limits.entry(*client_id).or_default();
+        sent.retain(|&sent_at| now - sent_at < CHAT_RATE_WINDOW);
+        if sent.len() >= CHAT_RATE_LIMIT {
+            debug!("client {client_id} is sending chat messages too fast");
+            received_events.send(ToClients {
+                mode: SendMode::Direct(*client_id),
+                event: ChatReceived::notice("You are sending messages too fast."),
+            });
+            continue;
+        }
+        sent.push(now);
+
+        received_events.send(ToClients {
+            mode: SendMode::Broadcast,
+            event: ChatReceived::new(name.name(), text),
+        });
+    }
+}
+
+fn clear_rate_limits(mut rate_limits: ResMut<ChatRateLimits>) {
+    rate_limits.clear();
+}
@@ -1,38 +1,52 @@
 use std::{
-    net::{Ipv4Addr, SocketAddr, UdpSocket},
-    time::SystemTime,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr, UdpSocket},
+    time::{Duration, SystemTime},
 };
 
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    tasks::{block_on, poll_once, AsyncComputeTaskPool, Task},
+};
 use bevy_replicon::{
-    client_connected,
+    client_connected, client_disconnected,
     prelude::*,
     renet::{
-        transport::{ClientAuthentication, NetcodeClientTransport},
+        transport::{
+            generate_random_bytes, ClientAuthentication, NetcodeClientTransport,
+            NetcodeDisconnectReason,
+        },
         ConnectionConfig,
     },
 };
 
 use crate::{
-    components::{Player, Symbol},
-    resources::{CurrentTurn, ServerConnectionInfo},
+    components::{Player, Spectator},
+    config::Config,
+    events::{ConnectionRejected, RejectReason},
+    profile::Profile,
+    resources::ServerConnectionInfo,
     state::GameState,
-    utils::any_component_added,
-    PORT, PROTOCOL_ID,
+    utils::{any_component_added, tear_down_with_component},
+    PROTOCOL_ID,
 };
 
+use super::{ConnectUserData, SessionToken, TokenIssuer};
+
+/// How long the client keeps trying to get back into a match after the connection dropped.
+const RECONNECT_TIMEOUT: Duration = Duration::from_secs(30);
+/// Delay between two reconnection attempts.
+const RECONNECT_INTERVAL: Duration = Duration::from_secs(3);
+
 pub struct ClientNetworkPlugin;
 
 impl Plugin for ClientNetworkPlugin {
     fn build(&self, app: &mut App) {
         {
+            app.add_systems(OnEnter(GameState::WaitingConnection), start_connection);
+            app.add_systems(OnExit(GameState::WaitingConnection), cancel_resolution);
             app.add_systems(
-                OnEnter(GameState::WaitingConnection),
-                start_connection.map(|r| {
-                    if let Err(e) = r {
-                        error!("Failed to start connection: {}", e);
-                    }
-                }),
+                Update,
+                connect_when_resolved.run_if(resource_exists::<ServerResolution>),
             );
             app.add_systems(
                 Update,
@@ -40,15 +54,107 @@ impl Plugin for ClientNetworkPlugin {
                     .run_if(client_connected)
                     .run_if(any_component_added::<Player>),
             );
+            app.add_systems(
+                Update,
+                client_connection_lost
+                    .run_if(resource_exists::<NetcodeClientTransport>)
+                    .run_if(client_disconnected)
+                    .run_if(in_state(GameState::Playing)),
+            );
+            app.add_systems(
+                OnEnter(GameState::Reconnecting),
+                (
+                    tear_down_with_component::<Player>,
+                    tear_down_with_component::<Spectator>,
+                    start_reconnecting,
+                ),
+            );
+            app.add_systems(
+                Update,
+                reconnect_system.run_if(in_state(GameState::Reconnecting)),
+            );
+            app.add_systems(
+                OnExit(GameState::Reconnecting),
+                (stop_reconnecting, cancel_resolution),
+            );
+            app.add_systems(
+                Update,
+                client_rejected.run_if(on_event::<ConnectionRejected>()),
+            );
         }
     }
 }
 
-/// Runs on the client side to connect to the server.
-fn start_connection(
+/// Why the server rejected the connection, shown to the player.
+#[derive(Resource, Deref)]
+pub struct ConnectionRejection(RejectReason);
+
+/// Looks up the address of the server without blocking the frame, the client connects once it's found.
+#[derive(Resource)]
+struct ServerResolution(Task<anyhow::Result<SocketAddr>>);
+
+impl ServerResolution {
+    fn start(server_config: &ServerConnectionInfo) -> Self {
+        let server_config = server_config.clone();
+        Self(AsyncComputeTaskPool::get().spawn(async move { server_config.resolve() }))
+    }
+}
+
+/// Tracks the attempts to reconnect to a match.
+#[derive(Resource)]
+struct Reconnection {
+    timeout: Timer,
+    retry: Timer,
+}
+
+/// Runs on the client side to connect to the server, once its address is resolved.
+fn start_connection(mut commands: Commands, server_config: Res<ServerConnectionInfo>) {
+    // A fresh connection always starts a new session.
+    commands.insert_resource(SessionToken::generate());
+    commands.remove_resource::<ConnectionRejection>();
+    commands.insert_resource(ServerResolution::start(&server_config));
+}
+
+/// Connects to the server once its address is resolved.
+fn connect_when_resolved(
     mut commands: Commands,
+    mut resolution: ResMut<ServerResolution>,
     network_channels: Res<NetworkChannels>,
     server_config: Res<ServerConnectionInfo>,
+    config: Res<Config>,
+    profile: Res<Profile>,
+    session_token: Res<SessionToken>,
+) {
+    let Some(server_addr) = block_on(poll_once(&mut resolution.0)) else {
+        return;
+    };
+    commands.remove_resource::<ServerResolution>();
+    if let Err(e) = server_addr.and_then(|server_addr| {
+        connect(
+            &mut commands,
+            &network_channels,
+            server_addr,
+            &server_config,
+            &config,
+            &profile,
+            *session_token,
+        )
+    }) {
+        error!("Failed to start connection: {}", e);
+    }
+}
+
+/// Creates the client and its transport, presenting the session token to the server.
+///
+/// Uses a connect token when a private key is configured.
+fn connect(
+    commands: &mut Commands,
+    network_channels: &NetworkChannels,
+    server_addr: SocketAddr,
+    server_config: &ServerConnectionInfo,
+    config: &Config,
+    profile: &Profile,
+    session_token: SessionToken,
 ) -> anyhow::Result<()> {
     let server_channels_config = network_channels.get_server_configs();
     let client_channels_config = network_channels.get_client_configs();
@@ -60,27 +166,116 @@ fn start_connection(
     });
 
     let current_time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
-    let client_id = current_time.as_millis() as u64;
-    let server_addr = SocketAddr::new(server_config.server_addr, PORT);
     info!("connecting to server at {}", server_addr);
-    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
-        .inspect_err(|e| error!("Failed to create udp socket. {e}"))?;
-    let authentication = ClientAuthentication::Unsecure {
-        client_id,
-        protocol_id: PROTOCOL_ID,
-        server_addr,
-        user_data: None,
+    let local_addr = match server_addr {
+        SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+        SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+    };
+    let socket =
+        UdpSocket::bind(local_addr).inspect_err(|e| error!("Failed to create udp socket. {e}"))?;
+    let user_data = ConnectUserData::new(
+        session_token,
+        profile,
+        &config.network.room,
+        server_config.password.as_deref(),
+    );
+    let authentication = match config.network.private_key()? {
+        Some(private_key) => ClientAuthentication::Secure {
+            connect_token: TokenIssuer::new(private_key).issue(
+                current_time,
+                server_addr,
+                &user_data,
+            )?,
+        },
+        None => ClientAuthentication::Unsecure {
+            client_id: u64::from_le_bytes(generate_random_bytes()),
+            protocol_id: PROTOCOL_ID,
+            server_addr,
+            user_data: Some(user_data.to_bytes()?),
+        },
     };
     let transport = NetcodeClientTransport::new(current_time, authentication, socket)
         .inspect_err(|e| error!("Failed on netcode client transport. {e}"))?;
 
     commands.insert_resource(client);
     commands.insert_resource(transport);
-    // Set the current turn to black.
-    commands.insert_resource(CurrentTurn::new(Symbol::Cross));
     Ok(())
 }
 
 fn client_start_game(mut state: ResMut<NextState<GameState>>) {
     state.set(GameState::Playing);
 }
+
+/// The server sends the reason right before disconnecting us.
+fn client_rejected(
+    mut commands: Commands,
+    mut rejected_events: EventReader<ConnectionRejected>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    for event in rejected_events.read() {
+        info!("connection rejected: {:?}", event.reason());
+        commands.insert_resource(ConnectionRejection(event.reason()));
+        state.set(GameState::Disconnected);
+    }
+}
+
+/// Tries to get back into the match when the connection drops, unless the server closed it.
+fn client_connection_lost(
+    transport: Res<NetcodeClientTransport>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    match transport.disconnect_reason() {
+        Some(NetcodeDisconnectReason::DisconnectedByServer) => {
+            info!("disconnected by the server");
+            state.set(GameState::Disconnected);
+        }
+        reason => {
+            info!("connection lost ({:?}), reconnecting", reason);
+            state.set(GameState::Reconnecting);
+        }
+    }
+}
+
+fn start_reconnecting(mut commands: Commands) {
+    let mut retry = Timer::new(RECONNECT_INTERVAL, TimerMode::Repeating);
+    // Make the first attempt right away.
+    retry.set_elapsed(RECONNECT_INTERVAL);
+    commands.insert_resource(Reconnection {
+        timeout: Timer::new(RECONNECT_TIMEOUT, TimerMode::Once),
+        retry,
+    });
+}
+
+fn stop_reconnecting(mut commands: Commands) {
+    commands.remove_resource::<Reconnection>();
+}
+
+/// Drops the lookup still running when the player leaves, so it doesn't connect later.
+fn cancel_resolution(mut commands: Commands) {
+    commands.remove_resource::<ServerResolution>();
+}
+
+/// Periodically reconnects with the same session token until the server seats us again.
+///
+/// The game restarts once the players are replicated, see [`client_start_game`].
+fn reconnect_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut reconnection: ResMut<Reconnection>,
+    mut state: ResMut<NextState<GameState>>,
+    client: Option<Res<RenetClient>>,
+    resolution: Option<Res<ServerResolution>>,
+    server_config: Res<ServerConnectionInfo>,
+) {
+    if reconnection.timeout.tick(time.delta()).just_finished() {
+        info!("failed to reconnect in {:?}", RECONNECT_TIMEOUT);
+        state.set(GameState::Disconnected);
+        return;
+    }
+
+    // The address is resolved again, the host may have moved.
+    let connecting = resolution.is_some() || client.is_some_and(|client| !client.is_disconnected());
+    if reconnection.retry.tick(time.delta()).just_finished() && !connecting {
+        commands.insert_resource(ServerResolution::start(&server_config));
+    }
+}
@@ -1,20 +1,35 @@
+mod chat;
 mod client;
 mod discovery;
+mod recording;
 mod server;
+mod session;
+mod token;
 
+pub use chat::*;
 pub use client::*;
 pub use discovery::*;
+pub use recording::*;
 pub use server::*;
+pub use session::*;
+pub use token::*;
 
 use bevy::prelude::*;
+use bevy_lan_discovery::Advertisement;
 use bevy_replicon::{
     prelude::*,
     renet::transport::{NetcodeClientTransport, NetcodeServerTransport},
 };
 
 use crate::{
-    components::{CellIndex, Player, Symbol},
-    events::CellPick,
+    components::{
+        AvatarColor, Away, CellIndex, Clock, MatchState, Player, PlayerName, Rating, Score, Series,
+        Spectator, Symbol,
+    },
+    events::{
+        CellPick, ChatMessage, ChatReceived, ConnectionRejected, MoveRejected, RematchRequest,
+        Takeback,
+    },
     state::GameState,
 };
 
@@ -23,14 +38,35 @@ pub struct NetworkPlugin;
 impl Plugin for NetworkPlugin {
     fn build(&self, app: &mut App) {
         {
-            app.add_plugins(ReplicationPlugins);
+            // Clients only see the match once the server accepted them.
+            app.add_plugins(ReplicationPlugins.set(ServerPlugin {
+                visibility_policy: VisibilityPolicy::Whitelist,
+                ..default()
+            }));
             app.replicate::<Symbol>();
             app.replicate::<CellIndex>();
             app.replicate::<Player>();
+            app.replicate::<PlayerName>();
+            app.replicate::<AvatarColor>();
+            app.replicate::<Spectator>();
+            app.replicate::<Away>();
+            app.replicate::<MatchState>();
+            app.replicate::<Clock>();
+            app.replicate::<Score>();
+            app.replicate::<Rating>();
+            app.replicate::<Series>();
             app.add_client_event::<CellPick>(EventType::Ordered);
+            app.add_client_event::<Takeback>(EventType::Ordered);
+            app.add_client_event::<RematchRequest>(EventType::Ordered);
+            app.add_client_event::<ChatMessage>(EventType::Ordered);
+            app.add_server_event::<ConnectionRejected>(EventType::Ordered);
+            app.add_server_event::<ChatReceived>(EventType::Ordered);
+            app.add_server_event::<MoveRejected>(EventType::Ordered);
+            app.add_plugins(ChatNetworkPlugin);
             app.add_plugins(ClientNetworkPlugin);
             app.add_plugins(ServerNetworkPlugin);
-            app.add_plugins(DiscoveryPlugin);
+            app.add_plugins(RecordingPlugin);
+            app.add_plugins(discovery_plugin());
             app.add_systems(OnEnter(GameState::MainMenu), tear_down_network);
         }
     }
@@ -41,8 +77,7 @@ fn tear_down_network(
     mut server: Option<ResMut<RenetServer>>,
     mut client: Option<ResMut<RenetClient>>,
     mut players: Option<ResMut<PlayersInGame>>,
-    mut discovery_client_state: ResMut<NextState<DiscoveryClientState>>,
-    mut discovery_server_state: ResMut<NextState<DiscoveryServerState>>,
+    spectators: Query<Entity, With<Spectator>>,
 ) {
     if let Some(mut server) = server.take() {
         info!("tearing down server");
@@ -59,11 +94,231 @@ fn tear_down_network(
         players.players.clear();
         info!("tearing down players");
     }
+    for spectator_entity in spectators.iter() {
+        commands.entity(spectator_entity).despawn_recursive();
+    }
     info!("tearing down network resources");
-    discovery_client_state.set(DiscoveryClientState::Stopped);
-    discovery_server_state.set(DiscoveryServerState::Stopped);
     commands.remove_resource::<RenetServer>();
     commands.remove_resource::<NetcodeServerTransport>();
+    // Stops the discovery server and client.
+    commands.remove_resource::<Advertisement<ServerInfo>>();
+    commands.remove_resource::<DiscoverServers>();
     commands.remove_resource::<RenetClient>();
     commands.remove_resource::<NetcodeClientTransport>();
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::event::Events;
+    use bevy_replicon::renet::ClientId;
+
+    use super::*;
+    use crate::{
+        config::Config,
+        events::{PickRejection, RejectReason},
+        resources::{MatchPassword, Winner},
+        testing::{board, client_app, host_app, play_online, run_until, state},
+    };
+
+    /// Index of the host app, the clients follow it.
+    const HOST: usize = 0;
+    const GUEST: usize = 1;
+    const SPECTATOR: usize = 2;
+
+    fn in_state(app: &App, game_state: GameState) -> bool {
+        state(app) == game_state
+    }
+
+    fn client_id(app: &App) -> ClientId {
+        app.world.resource::<NetcodeClientTransport>().client_id()
+    }
+
+    /// Names and symbols of the players replicated to the app, by client.
+    fn seats(app: &mut App) -> Vec<(ClientId, String, Symbol)> {
+        let mut seats: Vec<_> = app
+            .world
+            .query::<(&Player, &PlayerName, &Symbol)>()
+            .iter(&app.world)
+            .map(|(player, name, &symbol)| (player.client_id(), name.name().to_string(), symbol))
+            .collect();
+        seats.sort_by_key(|&(.., symbol)| symbol == Symbol::Nought);
+        seats
+    }
+
+    /// A host and a guest, connected and seated.
+    fn hosted_match() -> Vec<App> {
+        let (host, port) = host_app("Alice");
+        let guest = client_app("Bob", port);
+        let mut apps = vec![host, guest];
+        assert!(
+            run_until(&mut apps, |apps| apps
+                .iter()
+                .all(|app| in_state(app, GameState::Playing))),
+            "the guest never joined the match"
+        );
+        apps
+    }
+
+    #[test]
+    fn guest_wins_over_loopback() {
+        let mut apps = hosted_match();
+        let guest_id = client_id(&apps[GUEST]);
+        let expected_seats = vec![
+            (SERVER_ID, "Alice".to_string(), Symbol::Cross),
+            (guest_id, "Bob".to_string(), Symbol::Nought),
+        ];
+        for app in &mut apps {
+            assert_eq!(seats(app), expected_seats);
+        }
+
+        play_online(
+            &mut apps,
+            &[
+                (HOST, 0),
+                (GUEST, 3),
+                (HOST, 1),
+                (GUEST, 4),
+                (HOST, 8),
+                (GUEST, 5),
+            ],
+        );
+        assert!(run_until(&mut apps, |apps| apps
+            .iter()
+            .all(|app| in_state(app, GameState::GameOver))));
+
+        let host_board = board(&mut apps[HOST]);
+        assert_eq!(host_board[3..6], [Some(Symbol::Nought); 3]);
+        assert_eq!(board(&mut apps[GUEST]), host_board);
+        for app in &apps {
+            assert_eq!(app.world.resource::<Winner>().client_id(), Some(guest_id));
+        }
+    }
+
+    #[test]
+    fn spectator_watches_a_draw() {
+        let mut apps = hosted_match();
+        let port = apps[HOST].world.resource::<Config>().network.port;
+        apps.push(client_app("Carol", port));
+        assert!(
+            run_until(&mut apps, |apps| in_state(
+                &apps[SPECTATOR],
+                GameState::Playing
+            )),
+            "the spectator never joined the match"
+        );
+        let spectator_id = client_id(&apps[SPECTATOR]);
+        assert!(apps[HOST]
+            .world
+            .query::<&Spectator>()
+            .iter(&apps[HOST].world)
+            .any(|spectator| spectator.client_id() == spectator_id));
+        assert_eq!(seats(&mut apps[SPECTATOR]), seats(&mut apps[HOST]));
+
+        play_online(
+            &mut apps,
+            &[
+                (HOST, 0),
+                (GUEST, 1),
+                (HOST, 2),
+                (GUEST, 4),
+                (HOST, 3),
+                (GUEST, 5),
+                (HOST, 7),
+                (GUEST, 6),
+                (HOST, 8),
+            ],
+        );
+        assert!(run_until(&mut apps, |apps| apps
+            .iter()
+            .all(|app| in_state(app, GameState::Draw))));
+
+        let host_board = board(&mut apps[HOST]);
+        assert!(host_board.iter().all(Option::is_some));
+        assert_eq!(board(&mut apps[SPECTATOR]), host_board);
+    }
+
+    #[test]
+    fn guest_is_told_why_its_pick_was_rejected() {
+        let mut apps = hosted_match();
+        play_online(&mut apps, &[(HOST, 4)]);
+
+        // The host plays cross, so it's the guest's turn but the cell is taken.
+        apps[GUEST].world.send_event(CellPick::new(4));
+        assert!(
+            run_until(&mut apps, |apps| {
+                let events = apps[GUEST].world.resource::<Events<MoveRejected>>();
+                let rejected = events
+                    .get_reader()
+                    .read(events)
+                    .any(|event| event.reason() == PickRejection::Occupied);
+                rejected
+            }),
+            "the guest never learned its pick was rejected"
+        );
+        assert_eq!(board(&mut apps[HOST]).iter().flatten().count(), 1);
+    }
+
+    #[test]
+    fn new_guest_is_seated_after_the_previous_one_left() {
+        let mut apps = hosted_match();
+        play_online(
+            &mut apps,
+            &[(HOST, 0), (GUEST, 3), (HOST, 1), (GUEST, 4), (HOST, 2)],
+        );
+        assert!(run_until(&mut apps, |apps| in_state(
+            &apps[HOST],
+            GameState::GameOver
+        )));
+
+        // Leaving once the match is over doesn't keep the seat.
+        let mut guest = apps.pop().unwrap();
+        guest.world.resource_mut::<RenetClient>().disconnect();
+        guest.update();
+        assert!(run_until(&mut apps, |apps| in_state(
+            &apps[HOST],
+            GameState::Disconnected
+        )));
+
+        let port = apps[HOST].world.resource::<Config>().network.port;
+        apps.push(client_app("Carol", port));
+        assert!(
+            run_until(&mut apps, |apps| apps
+                .iter()
+                .all(|app| in_state(app, GameState::Playing))),
+            "the new guest never joined the match"
+        );
+        let names: Vec<_> = seats(&mut apps[GUEST])
+            .into_iter()
+            .map(|(_, name, _)| name)
+            .collect();
+        assert_eq!(names, ["Alice", "Carol"]);
+    }
+
+    #[test]
+    fn wrong_password_is_rejected_without_seeing_the_match() {
+        let (mut host, port) = host_app("Alice");
+        **host.world.resource_mut::<MatchPassword>() = Some("secret".to_string());
+        let guest = client_app("Bob", port);
+        let mut apps = vec![host, guest];
+        assert!(
+            run_until(&mut apps, |apps| {
+                let guest = &mut apps[GUEST];
+                assert!(
+                    seats(guest).is_empty(),
+                    "the rejected guest saw the players"
+                );
+                assert_ne!(state(guest), GameState::Playing);
+                guest
+                    .world
+                    .get_resource::<ConnectionRejection>()
+                    .is_some_and(|rejection| **rejection == RejectReason::WrongPassword)
+            }),
+            "the guest was never told its password was wrong"
+        );
+        assert!(run_until(&mut apps, |apps| in_state(
+            &apps[GUEST],
+            GameState::Disconnected
+        )));
+        assert_eq!(seats(&mut apps[HOST]).len(), 1);
+    }
+}
@@ -0,0 +1,207 @@
+use std::{
+    path::PathBuf,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use bevy_replicon::{prelude::*, renet::ServerEvent};
+
+use crate::{
+    components::{CellIndex, MatchState, Player, PlayerName, Symbol},
+    config::Config,
+    events::{PickAccepted, PickRejected},
+    game::MoveHistory,
+    notation::{self, square, NotatedGame, NotatedResult},
+    state::GameState,
+    stats::GameMode,
+};
+
+/// Writes every match hosted by this server to a game record, with the log of what the server saw.
+pub struct RecordingPlugin;
+
+impl Plugin for RecordingPlugin {
+    fn build(&self, app: &mut App) {
+        {
+            // After the game systems, so the move that ends the match is logged before the record is written.
+            app.add_systems(
+                PostUpdate,
+                (
+                    start_match_recording
+                        .run_if(recording_enabled)
+                        .run_if(any_match_started),
+                    log_server_events.run_if(resource_exists::<MatchRecording>),
+                )
+                    .chain()
+                    .run_if(resource_exists::<RenetServer>),
+            );
+            // The match either ended or was left.
+            app.add_systems(
+                OnExit(GameState::Playing),
+                write_match_recording.run_if(resource_exists::<MatchRecording>),
+            );
+            app.add_systems(OnEnter(GameState::MainMenu), remove_match_recording);
+        }
+    }
+}
+
+/// Log of the match being hosted.
+///
+/// Only for server.
+#[derive(Resource)]
+struct MatchRecording {
+    /// File the match was written to, the same file is updated if a takeback reopens the match.
+    path: Option<PathBuf>,
+    started_at: Duration,
+    /// Moves played when the log was last updated, to notice takebacks.
+    moves: usize,
+    log: Vec<String>,
+}
+
+impl MatchRecording {
+    fn push(&mut self, time: &Time, entry: impl Into<String>) {
+        let elapsed = time.elapsed().saturating_sub(self.started_at);
+        self.log
+            .push(format!("{:.3} {}", elapsed.as_secs_f32(), entry.into()));
+    }
+}
+
+fn recording_enabled(config: Res<Config>) -> bool {
+    config.network.record_matches
+}
+
+fn any_match_started(match_states: Query<(), Added<MatchState>>) -> bool {
+    !match_states.is_empty()
+}
+
+/// Starts a new log for each match, including rematches.
+fn start_match_recording(
+    mut commands: Commands,
+    time: Res<Time>,
+    players: Query<(&Player, &PlayerName, &Symbol)>,
+) {
+    let mut recording = MatchRecording {
+        path: None,
+        started_at: time.elapsed(),
+        moves: 0,
+        log: Vec::new(),
+    };
+    let since_epoch = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    recording.push(
+        &time,
+        format!("match started at {} (Unix time)", since_epoch.as_secs()),
+    );
+    for (player, name, symbol) in &players {
+        recording.push(
+            &time,
+            format!(
+                "{symbol:?} is {:?}, client {}",
+                name.name(),
+                player.client_id()
+            ),
+        );
+    }
+    info!("recording the match");
+    commands.insert_resource(recording);
+}
+
+/// Logs connections, picks and takebacks, with the time since the match started.
+fn log_server_events(
+    time: Res<Time>,
+    mut recording: ResMut<MatchRecording>,
+    mut server_events: EventReader<ServerEvent>,
+    mut accepted_events: EventReader<PickAccepted>,
+    mut rejected_events: EventReader<PickRejected>,
+    match_state: Query<&MatchState>,
+) {
+    for event in server_events.read() {
+        match event {
+            ServerEvent::ClientConnected { client_id } => {
+                recording.push(&time, format!("client {client_id} connected"));
+            }
+            ServerEvent::ClientDisconnected { client_id, reason } => {
+                recording.push(&time, format!("client {client_id} disconnected: {reason}"));
+            }
+        }
+    }
+    for event in accepted_events.read() {
+        recording.push(
+            &time,
+            format!("client {} played {}", event.client_id, square(event.index)),
+        );
+    }
+    for event in rejected_events.read() {
+        // The cell may not exist, so it's written as an index.
+        recording.push(
+            &time,
+            format!(
+                "client {} picked cell {}, rejected: {}",
+                event.client_id, event.index, event.reason
+            ),
+        );
+    }
+
+    let Ok(match_state) = match_state.get_single() else {
+        return;
+    };
+    let moves = match_state.move_number();
+    let logged_moves = recording.moves;
+    if moves < logged_moves {
+        recording.push(&time, format!("move {logged_moves} taken back"));
+    }
+    recording.moves = moves;
+}
+
+/// Writes the match to its record, unfinished matches get the `*` result.
+fn write_match_recording(
+    time: Res<Time>,
+    mut recording: ResMut<MatchRecording>,
+    history: Res<MoveHistory>,
+    match_state: Query<&MatchState>,
+    players: Query<(&PlayerName, &Symbol), With<Player>>,
+    cells: Query<&CellIndex>,
+) {
+    let result = match_state
+        .get_single()
+        .map_or(NotatedResult::Unfinished, |match_state| {
+            match_state.status().into()
+        });
+    recording.push(&time, format!("result {result}"));
+
+    let name_of = |symbol: Symbol| {
+        players
+            .iter()
+            .find(|(_, &player_symbol)| player_symbol == symbol)
+            .map_or("?".to_string(), |(name, _)| name.name().to_string())
+    };
+    let game = NotatedGame {
+        cross: name_of(Symbol::Cross),
+        nought: name_of(Symbol::Nought),
+        mode: GameMode::Hosted.to_string(),
+        date: notation::today(),
+        result,
+        moves: history
+            .iter()
+            .filter_map(|&entity| cells.get(entity).ok())
+            .map(CellIndex::index)
+            .collect(),
+        log: recording.log.clone(),
+    };
+
+    let path = match recording.path.clone() {
+        Some(path) => Ok(path),
+        None => notation::new_record_path("match"),
+    };
+    match path.and_then(|path| game.write(&path).map(|()| path)) {
+        Ok(path) => {
+            info!("recorded the match to {}", path.display());
+            recording.path = Some(path);
+        }
+        Err(e) => error!("Failed to record the match: {e}"),
+    }
+}
+
+fn remove_match_recording(mut commands: Commands) {
+    commands.remove_resource::<MatchRecording>();
+}
@@ -1,25 +1,46 @@
 use std::{
-    net::{Ipv4Addr, SocketAddr, UdpSocket},
-    time::SystemTime,
+    collections::HashMap,
+    net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, SocketAddrV6, UdpSocket},
+    time::{Duration, SystemTime},
 };
 
 use bevy::prelude::*;
+use bevy_lan_discovery::Advertisement;
 use bevy_replicon::{
     prelude::*,
     renet::{
-        transport::{NetcodeServerTransport, ServerAuthentication, ServerConfig},
-        ConnectionConfig, ServerEvent,
+        transport::{
+            generate_random_bytes, NetcodeServerTransport, ServerAuthentication, ServerConfig,
+        },
+        ClientId, ConnectionConfig, ServerEvent,
     },
 };
+use socket2::{Domain, Protocol, Socket, Type};
 
 use crate::{
-    components::{Player, PlayerBundle, Symbol},
-    resources::CurrentTurn,
+    components::{
+        AvatarColor, Away, Clock, MatchState, Player, PlayerBundle, Rating, Score, Spectator,
+        SpectatorBundle, Symbol,
+    },
+    config::{Config, DEFAULT_ROOM},
+    events::{ConnectionRejected, RejectReason},
+    profile::{PlayerId, Profile},
+    resources::MatchPassword,
+    save::SavedGame,
     state::GameState,
-    PORT, PROTOCOL_ID,
+    GRID_SIZE, PROTOCOL_ID,
 };
 
-use super::DiscoveryServerState;
+use super::{ConnectUserData, ServerInfo, SessionToken};
+
+/// Number of seats in a match.
+const SEATS: usize = 2;
+/// Maximum number of clients that can watch a match without being seated.
+const MAX_SPECTATORS: usize = 8;
+/// Time given to a rejected client to receive the reason before it's disconnected.
+const REJECT_DISCONNECT_DELAY: Duration = Duration::from_millis(500);
+/// How long the seat of a dropped player is kept before the match is forfeited.
+const RECONNECT_GRACE: Duration = Duration::from_secs(30);
 
 pub struct ServerNetworkPlugin;
 
@@ -35,6 +56,38 @@ impl Plugin for ServerNetworkPlugin {
                 Update,
                 server_handle_events.run_if(resource_exists::<RenetServer>),
             );
+            app.add_systems(
+                Update,
+                reconnect_grace_system.run_if(in_state(GameState::Playing)),
+            );
+            app.init_resource::<MatchPassword>();
+            app.init_resource::<PendingDisconnects>();
+            app.init_resource::<KnownRatings>();
+            app.add_systems(
+                Update,
+                remember_ratings.run_if(resource_exists::<RenetServer>),
+            );
+            app.add_systems(
+                Update,
+                pending_disconnect_system.run_if(resource_exists::<RenetServer>),
+            );
+            app.add_systems(
+                PostUpdate,
+                show_entities_to_accepted
+                    .before(ServerSet::Send)
+                    .run_if(resource_exists::<RenetServer>),
+            );
+            app.add_systems(
+                Update,
+                update_server_info
+                    .run_if(resource_exists::<Advertisement<ServerInfo>>)
+                    .run_if(resource_exists::<PlayersInGame>)
+                    // Every condition is evaluated, even after one failed.
+                    .run_if(
+                        resource_exists_and_changed::<PlayersInGame>
+                            .or_else(resource_changed::<MatchPassword>),
+                    ),
+            );
         }
     }
 }
@@ -57,9 +110,72 @@ impl PlayersInGame {
     }
 }
 
-fn start_hotseat_game(mut commands: Commands, mut state: ResMut<NextState<GameState>>) {
-    let player1 = commands.spawn(PlayerBundle::server(Symbol::Cross)).id();
-    let player2 = commands.spawn(PlayerBundle::server(Symbol::Nought)).id();
+/// Session of the client seated as this player, only known by the server.
+#[derive(Component, Deref)]
+struct PlayerSession(SessionToken);
+
+/// Rejected clients waiting to be disconnected.
+#[derive(Resource, Default, Deref, DerefMut)]
+struct PendingDisconnects(Vec<(ClientId, Timer)>);
+
+/// Profile of the client seated as this player, only known by the server.
+#[derive(Component, Deref)]
+struct KnownPlayer(PlayerId);
+
+/// Ratings the server gave to the players of its matches, by player id.
+///
+/// Kept after the players left, so a player that forfeited by leaving keeps the loss when rejoining.
+#[derive(Resource, Default, Deref, DerefMut)]
+struct KnownRatings(HashMap<PlayerId, Rating>);
+
+/// Counts down the time a dropped player has to reconnect.
+#[derive(Component, Deref, DerefMut)]
+struct ReconnectGrace(Timer);
+
+/// Seats both players on this computer, or the players of the saved game being continued.
+fn start_hotseat_game(
+    mut commands: Commands,
+    mut state: ResMut<NextState<GameState>>,
+    profile: Res<Profile>,
+    saved_game: Option<Res<SavedGame>>,
+) {
+    if let Some(saved_game) = saved_game {
+        let players: Vec<_> = saved_game
+            .players
+            .iter()
+            .map(|player| {
+                let mut entity = commands.spawn(PlayerBundle::server(
+                    player.symbol,
+                    player.name.clone(),
+                    player.avatar_color,
+                ));
+                entity.insert(Score::new(player.wins));
+                if let Some(remaining) = player.clock {
+                    entity.insert(Clock::new(remaining));
+                }
+                entity.id()
+            })
+            .collect();
+        commands.insert_resource(PlayersInGame::new(&players));
+        state.set(GameState::Playing);
+        return;
+    }
+
+    let symbol = profile.preferred_symbol;
+    let player1 = commands
+        .spawn(PlayerBundle::server(
+            symbol,
+            profile.name_or("Player 1"),
+            profile.avatar_color,
+        ))
+        .id();
+    let player2 = commands
+        .spawn(PlayerBundle::server(
+            symbol.next(),
+            "Player 2",
+            profile.avatar_color.next(),
+        ))
+        .id();
     commands.insert_resource(PlayersInGame::new(&[player1, player2]));
     state.set(GameState::Playing);
 }
@@ -68,7 +184,8 @@ fn start_hotseat_game(mut commands: Commands, mut state: ResMut<NextState<GameSt
 fn start_listening(
     mut commands: Commands,
     network_channels: Res<NetworkChannels>,
-    mut discovery_state_server: ResMut<NextState<DiscoveryServerState>>,
+    config: Res<Config>,
+    profile: Res<Profile>,
 ) -> anyhow::Result<()> {
     let server_channels_config = network_channels.get_server_configs();
     let client_channels_config = network_channels.get_client_configs();
@@ -80,52 +197,250 @@ fn start_listening(
     });
 
     let current_time = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?;
-    // We use the 0.0.0.0 (UNSPECIFIED)  address to listen on all available network interfaces.
-    let listen_addr = SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), PORT);
-    info!("listening for connections at {}", listen_addr);
-    let socket = UdpSocket::bind(listen_addr)?;
+    let port = config.network.port;
+    let socket = bind_dual_stack(port).or_else(|e| {
+        warn!("IPv6 unavailable ({}), listening on IPv4 only", e);
+        // We use the 0.0.0.0 (UNSPECIFIED)  address to listen on all available network interfaces.
+        UdpSocket::bind((Ipv4Addr::UNSPECIFIED, port))
+    })?;
+    info!("listening for connections at {}", socket.local_addr()?);
+    let authentication = match config.network.private_key()? {
+        Some(private_key) => {
+            info!("accepting only connect tokens signed with the configured key");
+            ServerAuthentication::Secure { private_key }
+        }
+        None => ServerAuthentication::Unsecure,
+    };
     let server_config = ServerConfig {
         current_time,
-        max_clients: 1,
+        // One seat is taken by the host, the rest of the connections can be players or spectators.
+        max_clients: SEATS - 1 + MAX_SPECTATORS,
         protocol_id: PROTOCOL_ID,
-        authentication: ServerAuthentication::Unsecure,
-        public_addresses: vec![listen_addr],
+        authentication,
+        public_addresses: public_addresses(port),
     };
     let transport = NetcodeServerTransport::new(server_config, socket)?;
 
     commands.insert_resource(server);
     commands.insert_resource(transport);
+    commands.insert_resource(KnownRatings::default());
 
-    let symbol = Symbol::Cross;
-    let player = commands.spawn(PlayerBundle::server(symbol)).id();
+    let player = spawn_host_player(&mut commands, &profile);
     commands.insert_resource(PlayersInGame::new(&[player]));
-    // Set the current turn to black.
-    commands.insert_resource(CurrentTurn::new(symbol));
     // Start the discovery server.
-    discovery_state_server.set(DiscoveryServerState::Running);
+    commands.insert_resource(Advertisement(ServerInfo {
+        server_id: u64::from_le_bytes(generate_random_bytes()),
+        host_name: profile.name_or("Host").to_string(),
+        host_rating: profile.rating,
+        game_port: port,
+        game_mode: "Classic".to_string(),
+        board_size: GRID_SIZE as u8,
+        seats_free: (SEATS - 1) as u8,
+        seats_total: SEATS as u8,
+        password: false,
+        protocol_id: PROTOCOL_ID,
+        build_version: env!("CARGO_PKG_VERSION").to_string(),
+    }));
     Ok(())
 }
 
+/// Seats the host, with the symbol it prefers.
+fn spawn_host_player(commands: &mut Commands, profile: &Profile) -> Entity {
+    commands
+        .spawn(PlayerBundle::server(
+            profile.preferred_symbol,
+            profile.name_or("Host"),
+            profile.avatar_color,
+        ))
+        .insert(Rating::new(profile.rating))
+        .id()
+}
+
+/// Binds a socket on all interfaces that accepts both IPv6 and IPv4 clients.
+fn bind_dual_stack(port: u16) -> std::io::Result<UdpSocket> {
+    let socket = Socket::new(Domain::IPV6, Type::DGRAM, Some(Protocol::UDP))?;
+    // IPv4 clients are seen as IPv4-mapped IPv6 addresses.
+    socket.set_only_v6(false)?;
+    socket.bind(&SocketAddr::from((Ipv6Addr::UNSPECIFIED, port)).into())?;
+    Ok(socket.into())
+}
+
+/// Addresses clients may use to reach this host.
+///
+/// Secure connect tokens are only accepted if they were issued for one of them.
+fn public_addresses(port: u16) -> Vec<SocketAddr> {
+    let mut addresses = vec![
+        SocketAddr::new(Ipv4Addr::UNSPECIFIED.into(), port),
+        SocketAddr::new(Ipv6Addr::UNSPECIFIED.into(), port),
+    ];
+    for interface in if_addrs::get_if_addrs().unwrap_or_default() {
+        let address = match interface.ip() {
+            // Clients reach link-local addresses through an interface, the token has its scope.
+            IpAddr::V6(ip) if ip.is_unicast_link_local() => match interface.index {
+                Some(index) => SocketAddrV6::new(ip, port, 0, index).into(),
+                None => continue,
+            },
+            ip => SocketAddr::new(ip, port),
+        };
+        addresses.push(address);
+    }
+    addresses
+}
+
+/// Seats the first connected client and turns every following connection into a spectator.
+///
+/// A client presenting the session token of a seated player takes that seat back.
 fn server_handle_events(
     mut commands: Commands,
     mut server_events: EventReader<ServerEvent>,
+    mut server: ResMut<RenetServer>,
+    transport: Res<NetcodeServerTransport>,
+    config: Res<Config>,
+    profile: Res<Profile>,
+    password: Res<MatchPassword>,
+    mut rejected_events: EventWriter<ToClients<ConnectionRejected>>,
+    mut pending_disconnects: ResMut<PendingDisconnects>,
+    known_ratings: Res<KnownRatings>,
+    state: Res<State<GameState>>,
     mut game_state: ResMut<NextState<GameState>>,
     mut players_in_game: ResMut<PlayersInGame>,
-    mut discovery_state_server: ResMut<NextState<DiscoveryServerState>>,
-    players: Query<&Symbol, With<Player>>,
+    mut players: Query<(Entity, &mut Player, &Symbol, Option<&PlayerSession>)>,
+    spectators: Query<(Entity, &Spectator)>,
 ) {
     for event in server_events.read() {
         match event {
             ServerEvent::ClientConnected { client_id } => {
-                info!("client connected: {}", client_id);
-                let server_player = players.single();
-                let stone = server_player.next();
-                let player = commands.spawn(PlayerBundle::new(*client_id, stone)).id();
-                players_in_game.add_player(player);
-                discovery_state_server.set(DiscoveryServerState::Stopped);
-                game_state.set(GameState::Playing);
+                let user_data = transport
+                    .user_data(*client_id)
+                    .and_then(|user_data| ConnectUserData::from_bytes(&user_data).ok());
+                // Clients without valid user data can only join an open match of the default room.
+                let (room, client_password) = user_data
+                    .as_ref()
+                    .map_or((DEFAULT_ROOM, &None), |user_data| {
+                        (user_data.room.as_str(), &user_data.password)
+                    });
+                let reason = if room != config.network.room {
+                    Some(RejectReason::WrongRoom)
+                } else if password.is_some() && *client_password != **password {
+                    Some(RejectReason::WrongPassword)
+                } else {
+                    None
+                };
+                if let Some(reason) = reason {
+                    info!("rejecting client {}: {:?}", client_id, reason);
+                    rejected_events.send(ToClients {
+                        mode: SendMode::Direct(*client_id),
+                        event: ConnectionRejected::new(reason),
+                    });
+                    pending_disconnects.push((
+                        *client_id,
+                        Timer::new(REJECT_DISCONNECT_DELAY, TimerMode::Once),
+                    ));
+                    continue;
+                }
+                let session_token = user_data.as_ref().map(|user_data| user_data.session_token);
+                let name = user_data
+                    .as_ref()
+                    .and_then(|user_data| user_data.player_name.clone())
+                    .unwrap_or_else(|| "Guest".to_string());
+                let avatar_color = user_data
+                    .as_ref()
+                    .map_or(AvatarColor::default(), |user_data| user_data.avatar_color);
+                let player_id = user_data.as_ref().and_then(|user_data| user_data.player_id);
+                // The rating given in this session wins over the one claimed by the profile.
+                let rating = player_id
+                    .and_then(|player_id| known_ratings.get(&player_id).copied())
+                    .or_else(|| {
+                        user_data
+                            .as_ref()
+                            .map(|user_data| Rating::new(user_data.rating))
+                    })
+                    .unwrap_or_default();
+
+                if let Some((player_entity, mut player, ..)) =
+                    players.iter_mut().find(|(.., session)| {
+                        session.is_some_and(|session| Some(**session) == session_token)
+                    })
+                {
+                    info!(
+                        "client {} reclaimed the seat of {}",
+                        client_id,
+                        player.client_id()
+                    );
+                    // The previous connection may not have timed out yet.
+                    if server.is_connected(player.client_id()) {
+                        server.disconnect(player.client_id());
+                    }
+                    player.set_client_id(*client_id);
+                    commands
+                        .entity(player_entity)
+                        .remove::<(Away, ReconnectGrace)>();
+                } else if players_in_game.players.len() < SEATS {
+                    info!("client connected: {}", client_id);
+                    let host = players
+                        .iter()
+                        .find(|(_, player, ..)| player.client_id() == SERVER_ID);
+                    let server_symbol = match host {
+                        Some((_, _, &symbol, _)) => symbol,
+                        // The players were removed when the previous guest left after its match.
+                        None => {
+                            info!("seating the host again");
+                            players_in_game.add_player(spawn_host_player(&mut commands, &profile));
+                            profile.preferred_symbol
+                        }
+                    };
+                    let stone = server_symbol.next();
+                    let mut player = commands.spawn((
+                        PlayerBundle::new(*client_id, stone, name, avatar_color),
+                        rating,
+                    ));
+                    if let Some(session_token) = session_token {
+                        player.insert(PlayerSession(session_token));
+                    }
+                    if let Some(player_id) = player_id {
+                        player.insert(KnownPlayer(player_id));
+                    }
+                    players_in_game.add_player(player.id());
+                    game_state.set(GameState::Playing);
+                } else {
+                    info!("spectator connected: {}", client_id);
+                    commands.spawn(SpectatorBundle::new(*client_id, name));
+                }
             }
             ServerEvent::ClientDisconnected { client_id, reason } => {
+                if let Some((spectator_entity, _)) = spectators
+                    .iter()
+                    .find(|(_, spectator)| spectator.client_id() == *client_id)
+                {
+                    info!(
+                        "spectator disconnected: {} with reason: {}",
+                        client_id, reason
+                    );
+                    commands.entity(spectator_entity).despawn_recursive();
+                    continue;
+                }
+
+                let Some((player_entity, ..)) = players
+                    .iter()
+                    .find(|(_, player, ..)| player.client_id() == *client_id)
+                else {
+                    // Connection replaced by a reconnect of the same session.
+                    debug!("ignoring disconnect of unseated client {}", client_id);
+                    continue;
+                };
+
+                if *state == GameState::Playing {
+                    info!(
+                        "client disconnected: {} with reason: {}, keeping its seat for {:?}",
+                        client_id, reason, RECONNECT_GRACE
+                    );
+                    commands.entity(player_entity).insert((
+                        Away,
+                        ReconnectGrace(Timer::new(RECONNECT_GRACE, TimerMode::Once)),
+                    ));
+                    continue;
+                }
+
                 info!("client disconnected: {} with reason: {}", client_id, reason);
                 for &player_entity in players_in_game.players.iter() {
                     commands.entity(player_entity).despawn_recursive();
@@ -136,3 +451,80 @@ fn server_handle_events(
         }
     }
 }
+
+/// Keeps the info answered to discovery in sync with the seats and the password.
+fn update_server_info(
+    mut info: ResMut<Advertisement<ServerInfo>>,
+    players_in_game: Res<PlayersInGame>,
+    password: Res<MatchPassword>,
+) {
+    info.seats_free = SEATS.saturating_sub(players_in_game.players.len()) as u8;
+    info.password = password.is_some();
+}
+
+/// Disconnects the rejected clients once they had time to receive the reason.
+fn pending_disconnect_system(
+    time: Res<Time>,
+    mut server: ResMut<RenetServer>,
+    mut pending_disconnects: ResMut<PendingDisconnects>,
+) {
+    pending_disconnects.retain_mut(|(client_id, timer)| {
+        if timer.tick(time.delta()).just_finished() {
+            server.disconnect(*client_id);
+            false
+        } else {
+            true
+        }
+    });
+}
+
+/// Replicates the match to every client except the rejected ones, which wait for the reason.
+fn show_entities_to_accepted(
+    mut client_cache: ResMut<ClientCache>,
+    pending_disconnects: Res<PendingDisconnects>,
+    replicated: Query<Entity, With<Replication>>,
+) {
+    for client in client_cache.iter_mut() {
+        let client_id = client.id();
+        if pending_disconnects.iter().any(|&(id, _)| id == client_id) {
+            continue;
+        }
+        for entity in &replicated {
+            client.visibility_mut().set_visibility(entity, true);
+        }
+    }
+}
+
+/// Records the ratings of the seated clients, including the ones given while they were away.
+fn remember_ratings(
+    mut known_ratings: ResMut<KnownRatings>,
+    players: Query<(&KnownPlayer, &Rating), Changed<Rating>>,
+) {
+    for (known_player, &rating) in &players {
+        known_ratings.insert(**known_player, rating);
+    }
+}
+
+/// Forfeits the match for players that didn't reconnect in time.
+fn reconnect_grace_system(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut match_state: Query<&mut MatchState>,
+    mut away_players: Query<(Entity, &Player, &mut ReconnectGrace)>,
+    present_players: Query<(&Player, &Symbol), Without<Away>>,
+) {
+    for (player_entity, player, mut grace) in &mut away_players {
+        if grace.tick(time.delta()).just_finished() {
+            info!(
+                "player {} didn't reconnect in time, forfeiting the match",
+                player.client_id()
+            );
+            commands.entity(player_entity).remove::<ReconnectGrace>();
+            if let (Ok(mut match_state), Some((winner, &symbol))) =
+                (match_state.get_single_mut(), present_players.iter().next())
+            {
+                match_state.set_won(Some(winner.client_id()), symbol, None);
+            }
+        }
+    }
+}
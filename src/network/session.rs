@@ -0,0 +1,81 @@
+use bevy::prelude::*;
+use bevy_replicon::{
+    bincode,
+    renet::transport::{generate_random_bytes, NETCODE_USER_DATA_BYTES},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::AvatarColor,
+    profile::{PlayerId, Profile},
+};
+
+/// Identifies a client across connections, so a dropped player can claim back its seat.
+#[derive(Resource, Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+pub struct SessionToken(u64);
+
+impl SessionToken {
+    /// Creates a new random token.
+    pub fn generate() -> Self {
+        Self(u64::from_le_bytes(generate_random_bytes()))
+    }
+}
+
+/// Maximum number of bytes of each text field, so the data fits in the netcode user data.
+const MAX_FIELD_BYTES: usize = 48;
+
+/// Data sent by the client inside the netcode user data when connecting.
+///
+/// With secure authentication it's part of the encrypted connect token.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ConnectUserData {
+    pub session_token: SessionToken,
+    pub player_id: Option<PlayerId>,
+    pub player_name: Option<String>,
+    pub avatar_color: AvatarColor,
+    /// Rating claimed by the profile, used by hosts that don't know the player yet.
+    pub rating: i32,
+    pub room: String,
+    pub password: Option<String>,
+}
+
+impl ConnectUserData {
+    pub fn new(
+        session_token: SessionToken,
+        profile: &Profile,
+        room: &str,
+        password: Option<&str>,
+    ) -> Self {
+        let player_name = Some(profile.name.trim()).filter(|name| !name.is_empty());
+        Self {
+            session_token,
+            player_id: profile.player_id,
+            player_name: player_name.map(truncate_field),
+            avatar_color: profile.avatar_color,
+            rating: profile.rating,
+            room: truncate_field(room),
+            password: password.map(truncate_field),
+        }
+    }
+
+    /// Serializes the data into the fixed size netcode user data buffer.
+    pub fn to_bytes(&self) -> anyhow::Result<[u8; NETCODE_USER_DATA_BYTES]> {
+        let mut bytes = [0; NETCODE_USER_DATA_BYTES];
+        bincode::serialize_into(&mut bytes[..], self)?;
+        Ok(bytes)
+    }
+
+    /// Deserializes the data from the netcode user data buffer.
+    pub fn from_bytes(bytes: &[u8; NETCODE_USER_DATA_BYTES]) -> anyhow::Result<Self> {
+        Ok(bincode::deserialize(bytes)?)
+    }
+}
+
+/// Cuts the text to [`MAX_FIELD_BYTES`] on a character boundary.
+fn truncate_field(text: &str) -> String {
+    let mut end = text.len().min(MAX_FIELD_BYTES);
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text[..end].to_string()
+}
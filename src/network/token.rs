@@ -0,0 +1,47 @@
+use std::{net::SocketAddr, time::Duration};
+
+use bevy_replicon::renet::transport::{generate_random_bytes, ConnectToken, NETCODE_KEY_BYTES};
+
+use crate::PROTOCOL_ID;
+
+use super::ConnectUserData;
+
+/// How long an issued token can be used to start a connection.
+const TOKEN_EXPIRE_SECONDS: u64 = 300;
+/// Time without packets before a connection is considered lost.
+const CONNECTION_TIMEOUT_SECONDS: i32 = 15;
+
+/// Issues netcode connect tokens for secure connections.
+///
+/// There is no backend to request tokens from, so the issuer runs on the client and
+/// hosts and clients need the same private key in their configuration.
+pub struct TokenIssuer {
+    private_key: [u8; NETCODE_KEY_BYTES],
+}
+
+impl TokenIssuer {
+    pub fn new(private_key: [u8; NETCODE_KEY_BYTES]) -> Self {
+        Self { private_key }
+    }
+
+    /// Issues a token for a random client ID that is valid only for the given server address.
+    pub fn issue(
+        &self,
+        current_time: Duration,
+        server_addr: SocketAddr,
+        user_data: &ConnectUserData,
+    ) -> anyhow::Result<ConnectToken> {
+        let client_id = u64::from_le_bytes(generate_random_bytes());
+        let connect_token = ConnectToken::generate(
+            current_time,
+            PROTOCOL_ID,
+            TOKEN_EXPIRE_SECONDS,
+            client_id,
+            CONNECTION_TIMEOUT_SECONDS,
+            vec![server_addr],
+            Some(&user_data.to_bytes()?),
+            &self.private_key,
+        )?;
+        Ok(connect_token)
+    }
+}
@@ -0,0 +1,696 @@
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use bevy_replicon::renet::transport::{NetcodeClientTransport, NetcodeServerTransport};
+
+use crate::{
+    components::{CellIndex, MatchState, MatchStatus, Player, PlayerName, Symbol},
+    game::{winning_line, MoveHistory},
+    stats::GameMode,
+    GRID_SIZE,
+};
+
+/// Extension of the game record files.
+pub const RECORD_EXTENSION: &str = "ttt";
+/// Directory the games are exported to, relative to the working directory.
+const RECORDS_DIR: &str = "games";
+
+/// Exports the finished games in a PGN-like text notation.
+pub struct NotationPlugin;
+
+impl Plugin for NotationPlugin {
+    fn build(&self, app: &mut App) {
+        {
+            app.add_event::<ExportGame>();
+            app.add_event::<GameExported>();
+            app.add_systems(Update, export_game.run_if(on_event::<ExportGame>()));
+        }
+    }
+}
+
+/// Asks to export the game on the board to [`RECORDS_DIR`].
+#[derive(Event)]
+pub struct ExportGame;
+
+/// Sent once the game was exported, with the file it was written to.
+///
+/// The path is `None` if the export failed.
+#[derive(Event)]
+pub struct GameExported {
+    pub path: Option<PathBuf>,
+}
+
+/// Outcome written at the end of a record, like in PGN.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum NotatedResult {
+    CrossWins,
+    NoughtWins,
+    Draw,
+    Unfinished,
+}
+
+impl NotatedResult {
+    fn from_token(token: &str) -> Option<Self> {
+        match token {
+            "1-0" => Some(NotatedResult::CrossWins),
+            "0-1" => Some(NotatedResult::NoughtWins),
+            "1/2-1/2" => Some(NotatedResult::Draw),
+            "*" => Some(NotatedResult::Unfinished),
+            _ => None,
+        }
+    }
+
+    fn won_by(symbol: Symbol) -> Self {
+        match symbol {
+            Symbol::Cross => NotatedResult::CrossWins,
+            Symbol::Nought => NotatedResult::NoughtWins,
+        }
+    }
+}
+
+impl From<MatchStatus> for NotatedResult {
+    fn from(status: MatchStatus) -> Self {
+        match status {
+            MatchStatus::InProgress => NotatedResult::Unfinished,
+            MatchStatus::Won { symbol, .. } => NotatedResult::won_by(symbol),
+            MatchStatus::Draw => NotatedResult::Draw,
+        }
+    }
+}
+
+impl fmt::Display for NotatedResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotatedResult::CrossWins => f.write_str("1-0"),
+            NotatedResult::NoughtWins => f.write_str("0-1"),
+            NotatedResult::Draw => f.write_str("1/2-1/2"),
+            NotatedResult::Unfinished => f.write_str("*"),
+        }
+    }
+}
+
+/// A game written down to be shared, with its header tags and its moves.
+///
+/// ```text
+/// [Cross "Alice"]
+/// [Nought "Bob"]
+/// [Mode "Hotseat"]
+/// [Board "3x3"]
+/// [Date "2026.10.18"]
+/// [Result "1-0"]
+///
+/// 1. a3 a2 2. b3 b2 3. c3 1-0
+/// ```
+///
+/// Like in PGN, a `;` starts a comment that runs to the end of the line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct NotatedGame {
+    pub cross: String,
+    pub nought: String,
+    pub mode: String,
+    /// Day the game was played, `YYYY.MM.DD`.
+    pub date: String,
+    pub result: NotatedResult,
+    /// Cells in the order they were played, cross plays first.
+    pub moves: Vec<usize>,
+    /// Comment lines, written between the tags and the moves.
+    pub log: Vec<String>,
+}
+
+impl NotatedGame {
+    /// Reads and checks a game record.
+    pub fn import(path: &Path) -> anyhow::Result<Self> {
+        Ok(fs::read_to_string(path)?.parse()?)
+    }
+
+    /// The exported records, newest first.
+    pub fn exported() -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(RECORDS_DIR) else {
+            return Vec::new();
+        };
+        let mut records: Vec<_> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == RECORD_EXTENSION))
+            .collect();
+        records.sort_by_cached_key(|path| {
+            std::cmp::Reverse(
+                fs::metadata(path)
+                    .and_then(|metadata| metadata.modified())
+                    .ok(),
+            )
+        });
+        records
+    }
+
+    /// Writes the game to a new file of [`RECORDS_DIR`] and returns its path.
+    pub fn export(&self) -> anyhow::Result<PathBuf> {
+        let path = new_record_path("game")?;
+        self.write(&path)?;
+        Ok(path)
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        fs::write(path, self.to_string())?;
+        Ok(())
+    }
+}
+
+impl fmt::Display for NotatedGame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let board = format!("{GRID_SIZE}x{GRID_SIZE}");
+        let result = self.result.to_string();
+        for (name, value) in [
+            ("Cross", self.cross.as_str()),
+            ("Nought", self.nought.as_str()),
+            ("Mode", self.mode.as_str()),
+            ("Board", board.as_str()),
+            ("Date", self.date.as_str()),
+            ("Result", result.as_str()),
+        ] {
+            let value = value.replace('\\', "\\\\").replace('"', "\\\"");
+            writeln!(f, "[{name} \"{value}\"]")?;
+        }
+        writeln!(f)?;
+
+        for line in &self.log {
+            // A line break would end the comment.
+            writeln!(f, "; {}", line.replace(['\r', '\n'], " "))?;
+        }
+        for (index, &cell) in self.moves.iter().enumerate() {
+            if index % 2 == 0 {
+                write!(f, "{}. ", index / 2 + 1)?;
+            }
+            write!(f, "{} ", square(cell))?;
+        }
+        writeln!(f, "{}", self.result)
+    }
+}
+
+impl FromStr for NotatedGame {
+    type Err = ParseError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut tags: Vec<(String, String, Position)> = Vec::new();
+        let mut tokens = Vec::new();
+        let mut log = Vec::new();
+        for (line_index, line) in text.lines().enumerate() {
+            let position = |offset: usize| Position {
+                line: line_index + 1,
+                column: line[..offset].chars().count() + 1,
+            };
+            let trimmed = line.trim();
+            if trimmed.starts_with('[') {
+                let at = position(line.len() - line.trim_start().len());
+                if !tokens.is_empty() {
+                    return Err(ParseError::new(at, ParseErrorKind::TagAfterMoves));
+                }
+                let (name, value) =
+                    parse_tag(trimmed).ok_or(ParseError::new(at, ParseErrorKind::MalformedTag))?;
+                if tags.iter().any(|(tag, ..)| *tag == name) {
+                    return Err(ParseError::new(at, ParseErrorKind::DuplicateTag(name)));
+                }
+                tags.push((name, value, at));
+                continue;
+            }
+            let line = match line.split_once(';') {
+                Some((line, comment)) => {
+                    log.push(comment.strip_prefix(' ').unwrap_or(comment).to_string());
+                    line
+                }
+                None => line,
+            };
+            let mut offset = 0;
+            for word in line.split_whitespace() {
+                offset += line[offset..].find(word).unwrap_or(0);
+                tokens.push((word, position(offset)));
+                offset += word.len();
+            }
+        }
+        let end = Position {
+            line: text.lines().count().max(1),
+            column: text.lines().last().map_or(0, |line| line.chars().count()) + 1,
+        };
+
+        let tag = |name: &'static str| {
+            tags.iter()
+                .find(|(tag, ..)| tag == name)
+                .map(|(_, value, at)| (value.as_str(), *at))
+        };
+        let required = |name: &'static str| {
+            tag(name).ok_or(ParseError::new(end, ParseErrorKind::MissingTag(name)))
+        };
+        if let Some((board, at)) = tag("Board") {
+            if board != format!("{GRID_SIZE}x{GRID_SIZE}") {
+                return Err(ParseError::new(
+                    at,
+                    ParseErrorKind::UnsupportedBoard(board.to_string()),
+                ));
+            }
+        }
+        let (result, at) = required("Result")?;
+        let result = NotatedResult::from_token(result).ok_or(ParseError::new(
+            at,
+            ParseErrorKind::InvalidResult(result.to_string()),
+        ))?;
+
+        let mut board = [None; GRID_SIZE * GRID_SIZE];
+        let mut moves = Vec::new();
+        let mut final_result = None;
+        for (token, at) in tokens {
+            if final_result.is_some() {
+                return Err(ParseError::new(
+                    at,
+                    ParseErrorKind::TokenAfterResult(token.to_string()),
+                ));
+            }
+            if let Some(result) = NotatedResult::from_token(token) {
+                final_result = Some((result, at));
+                continue;
+            }
+
+            // Move numbers can be glued to the move, like `1.b2`.
+            let (mut token, mut at) = (token, at);
+            if let Some(dot) = token.find('.') {
+                let number = &token[..=dot];
+                let expected = (moves.len() % 2 == 0).then_some(moves.len() / 2 + 1);
+                if expected.is_none() || number[..dot].parse().ok() != expected {
+                    return Err(ParseError::new(
+                        at,
+                        ParseErrorKind::UnexpectedMoveNumber {
+                            found: number.to_string(),
+                            expected,
+                        },
+                    ));
+                }
+                token = &token[dot + 1..];
+                at.column += number.chars().count();
+                if token.is_empty() {
+                    continue;
+                }
+            }
+
+            let cell = parse_square(token).ok_or(ParseError::new(
+                at,
+                ParseErrorKind::InvalidSquare(token.to_string()),
+            ))?;
+            if winning_line(&board).is_some() || board.iter().all(Option::is_some) {
+                return Err(ParseError::new(
+                    at,
+                    ParseErrorKind::MoveAfterEnd(token.to_string()),
+                ));
+            }
+            if board[cell].is_some() {
+                return Err(ParseError::new(
+                    at,
+                    ParseErrorKind::OccupiedSquare(token.to_string()),
+                ));
+            }
+            board[cell] = Some(Symbol::of_move(moves.len()));
+            moves.push(cell);
+        }
+
+        let Some((final_result, at)) = final_result else {
+            return Err(ParseError::new(end, ParseErrorKind::MissingResult));
+        };
+        if final_result != result {
+            return Err(ParseError::new(
+                at,
+                ParseErrorKind::ResultMismatch {
+                    tag: result,
+                    movetext: final_result,
+                },
+            ));
+        }
+        let contradiction = match winning_line(&board) {
+            Some((symbol, _)) if result != NotatedResult::won_by(symbol) => Some(match symbol {
+                Symbol::Cross => "cross completed a line",
+                Symbol::Nought => "nought completed a line",
+            }),
+            Some(_) => None,
+            None if board.iter().all(Option::is_some) => {
+                (result != NotatedResult::Draw).then_some("the board is full without a line")
+            }
+            // A game can also be won on time or by forfeit.
+            None => (result == NotatedResult::Draw).then_some("the board isn't full"),
+        };
+        if let Some(reason) = contradiction {
+            return Err(ParseError::new(
+                at,
+                ParseErrorKind::ContradictedResult { result, reason },
+            ));
+        }
+
+        Ok(NotatedGame {
+            cross: required("Cross")?.0.to_string(),
+            nought: required("Nought")?.0.to_string(),
+            mode: tag("Mode").map_or("?", |(mode, _)| mode).to_string(),
+            date: tag("Date")
+                .map_or("????.??.??", |(date, _)| date)
+                .to_string(),
+            result,
+            moves,
+            log,
+        })
+    }
+}
+
+/// Where a record is malformed, counted from 1.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseError {
+    pub position: Position,
+    pub kind: ParseErrorKind,
+}
+
+impl ParseError {
+    fn new(position: Position, kind: ParseErrorKind) -> Self {
+        Self { position, kind }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.position.line, self.position.column, self.kind
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ParseErrorKind {
+    MalformedTag,
+    DuplicateTag(String),
+    TagAfterMoves,
+    MissingTag(&'static str),
+    UnsupportedBoard(String),
+    InvalidResult(String),
+    UnexpectedMoveNumber {
+        found: String,
+        expected: Option<usize>,
+    },
+    InvalidSquare(String),
+    OccupiedSquare(String),
+    MoveAfterEnd(String),
+    MissingResult,
+    TokenAfterResult(String),
+    ResultMismatch {
+        tag: NotatedResult,
+        movetext: NotatedResult,
+    },
+    ContradictedResult {
+        result: NotatedResult,
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for ParseErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseErrorKind::MalformedTag => f.write_str("malformed tag, expected [Name \"value\"]"),
+            ParseErrorKind::DuplicateTag(name) => write!(f, "tag {name} is given twice"),
+            ParseErrorKind::TagAfterMoves => f.write_str("tags must come before the moves"),
+            ParseErrorKind::MissingTag(name) => write!(f, "missing tag {name}"),
+            ParseErrorKind::UnsupportedBoard(board) => {
+                write!(
+                    f,
+                    "unsupported board {board}, only {GRID_SIZE}x{GRID_SIZE} is played"
+                )
+            }
+            ParseErrorKind::InvalidResult(result) => {
+                write!(
+                    f,
+                    "invalid result {result}, expected 1-0, 0-1, 1/2-1/2 or *"
+                )
+            }
+            ParseErrorKind::UnexpectedMoveNumber {
+                found,
+                expected: Some(expected),
+            } => write!(f, "unexpected move number {found}, expected {expected}"),
+            ParseErrorKind::UnexpectedMoveNumber {
+                found,
+                expected: None,
+            } => write!(f, "unexpected move number {found}, nought plays next"),
+            ParseErrorKind::InvalidSquare(square) => {
+                write!(f, "invalid square {square}, expected a1 to c3")
+            }
+            ParseErrorKind::OccupiedSquare(square) => {
+                write!(f, "square {square} is already taken")
+            }
+            ParseErrorKind::MoveAfterEnd(square) => {
+                write!(f, "move {square} is played after the end of the game")
+            }
+            ParseErrorKind::MissingResult => f.write_str("the moves don't end with a result"),
+            ParseErrorKind::TokenAfterResult(token) => {
+                write!(f, "unexpected {token} after the result")
+            }
+            ParseErrorKind::ResultMismatch { tag, movetext } => {
+                write!(f, "result {movetext} doesn't match the Result tag {tag}")
+            }
+            ParseErrorKind::ContradictedResult { result, reason } => {
+                write!(f, "result {result} is impossible, {reason}")
+            }
+        }
+    }
+}
+
+/// Square of a cell, files a to c from the left and ranks 1 to 3 from the bottom.
+pub fn square(cell: usize) -> String {
+    let file = char::from(b'a' + (cell % GRID_SIZE) as u8);
+    let rank = GRID_SIZE - cell / GRID_SIZE;
+    format!("{file}{rank}")
+}
+
+fn parse_square(square: &str) -> Option<usize> {
+    let mut chars = square.chars();
+    let (file, rank) = (chars.next()?, chars.next()?);
+    if chars.next().is_some() {
+        return None;
+    }
+    let file = (file as usize).checked_sub('a' as usize)?;
+    let rank = rank.to_digit(10)? as usize;
+    (file < GRID_SIZE && (1..=GRID_SIZE).contains(&rank))
+        .then(|| (GRID_SIZE - rank) * GRID_SIZE + file)
+}
+
+/// Splits `[Name "value"]` into its name and unescaped value.
+fn parse_tag(line: &str) -> Option<(String, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let (name, quoted) = inner.split_once(char::is_whitespace)?;
+    if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return None;
+    }
+    let quoted = quoted.trim().strip_prefix('"')?.strip_suffix('"')?;
+
+    let mut value = String::new();
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => value.push(chars.next()?),
+            '"' => return None,
+            c => value.push(c),
+        }
+    }
+    Some((name.to_string(), value))
+}
+
+/// A file of [`RECORDS_DIR`] that doesn't exist yet, named after the current time.
+pub fn new_record_path(prefix: &str) -> anyhow::Result<PathBuf> {
+    fs::create_dir_all(RECORDS_DIR)?;
+    let seconds = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+    let mut path = Path::new(RECORDS_DIR).join(format!("{prefix}-{seconds}.{RECORD_EXTENSION}"));
+    for copy in 2.. {
+        if !path.exists() {
+            break;
+        }
+        path = Path::new(RECORDS_DIR).join(format!("{prefix}-{seconds}-{copy}.{RECORD_EXTENSION}"));
+    }
+    Ok(path)
+}
+
+/// Today's date in the `YYYY.MM.DD` form of the Date tag.
+pub fn today() -> String {
+    let days = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |since_epoch| since_epoch.as_secs() / 86_400) as i64;
+    // Days to civil date, from Howard Hinnant's date algorithms.
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{year:04}.{month:02}.{day:02}")
+}
+
+fn export_game(
+    mut exported_events: EventWriter<GameExported>,
+    history: Res<MoveHistory>,
+    client_transport: Option<Res<NetcodeClientTransport>>,
+    server_transport: Option<Res<NetcodeServerTransport>>,
+    match_state: Query<&MatchState>,
+    players: Query<(&PlayerName, &Symbol), With<Player>>,
+    cells: Query<&CellIndex>,
+) {
+    let name_of = |symbol: Symbol| {
+        players
+            .iter()
+            .find(|(_, &player_symbol)| player_symbol == symbol)
+            .map_or("?".to_string(), |(name, _)| name.name().to_string())
+    };
+    let game = NotatedGame {
+        cross: name_of(Symbol::Cross),
+        nought: name_of(Symbol::Nought),
+        mode: GameMode::current(client_transport.as_deref(), server_transport.as_deref())
+            .to_string(),
+        date: today(),
+        result: match_state
+            .get_single()
+            .map_or(NotatedResult::Unfinished, |match_state| {
+                match_state.status().into()
+            }),
+        moves: history
+            .iter()
+            .filter_map(|&entity| cells.get(entity).ok())
+            .map(CellIndex::index)
+            .collect(),
+        log: Vec::new(),
+    };
+
+    let path = game
+        .export()
+        .inspect(|path| info!("exported the game to {}", path.display()))
+        .inspect_err(|e| error!("Failed to export the game: {e}"))
+        .ok();
+    exported_events.send(GameExported { path });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RECORD: &str = r#"[Cross "Alice \"Ace\""]
+[Nought "Bob"]
+[Mode "Hotseat"]
+[Board "3x3"]
+[Date "2026.10.18"]
+[Result "1-0"]
+
+1. a3 a2 2. b3 b2 3. c3 1-0
+"#;
+
+    fn error_of(record: &str) -> ParseError {
+        record.parse::<NotatedGame>().unwrap_err()
+    }
+
+    #[test]
+    fn round_trip() {
+        let game: NotatedGame = RECORD.parse().unwrap();
+        assert_eq!(game.cross, "Alice \"Ace\"");
+        assert_eq!(game.result, NotatedResult::CrossWins);
+        assert_eq!(game.moves, [0, 3, 1, 4, 2]);
+        assert_eq!(game.to_string(), RECORD);
+    }
+
+    #[test]
+    fn glued_move_numbers() {
+        let record = RECORD.replace("1. a3 a2 2. b3", "1.a3 a2 2.b3");
+        assert_eq!(record.parse::<NotatedGame>().unwrap().moves.len(), 5);
+    }
+
+    #[test]
+    fn comments() {
+        let record = RECORD.replace(
+            "\n1. a3",
+            "\n; 0.000 match started\n; 1.500 client 42 picked a3\n1. a3 ; accepted\n",
+        );
+        let game: NotatedGame = record.parse().unwrap();
+        assert_eq!(
+            game.log,
+            [
+                "0.000 match started",
+                "1.500 client 42 picked a3",
+                "accepted"
+            ]
+        );
+        assert_eq!(game.moves, [0, 3, 1, 4, 2]);
+
+        let written = game.to_string();
+        assert_eq!(written.parse::<NotatedGame>().unwrap(), game);
+    }
+
+    #[test]
+    fn malformed_tag() {
+        let error = error_of(&RECORD.replace("[Nought \"Bob\"]", "[Nought Bob]"));
+        assert_eq!(error.position, Position { line: 2, column: 1 });
+        assert_eq!(error.kind, ParseErrorKind::MalformedTag);
+    }
+
+    #[test]
+    fn illegal_moves() {
+        let error = error_of(&RECORD.replace("3. c3", "3. d3"));
+        assert_eq!(
+            error.position,
+            Position {
+                line: 8,
+                column: 22
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "line 8, column 22: invalid square d3, expected a1 to c3"
+        );
+
+        let error = error_of(&RECORD.replace("3. c3", "3. b2"));
+        assert_eq!(error.kind, ParseErrorKind::OccupiedSquare("b2".to_string()));
+
+        let error = error_of(&RECORD.replace("c3 1-0", "c3 a1 1-0"));
+        assert_eq!(error.kind, ParseErrorKind::MoveAfterEnd("a1".to_string()));
+    }
+
+    #[test]
+    fn wrong_move_number() {
+        let error = error_of(&RECORD.replace("3. c3", "4. c3"));
+        assert_eq!(
+            error.to_string(),
+            "line 8, column 19: unexpected move number 4., expected 3"
+        );
+    }
+
+    #[test]
+    fn inconsistent_results() {
+        let error = error_of(&RECORD.replace("c3 1-0", "c3"));
+        assert_eq!(error.kind, ParseErrorKind::MissingResult);
+
+        let error = error_of(&RECORD.replace("c3 1-0", "c3 0-1"));
+        assert!(matches!(error.kind, ParseErrorKind::ResultMismatch { .. }));
+
+        let error = error_of(&RECORD.replace("1-0", "0-1"));
+        assert_eq!(
+            error.to_string(),
+            "line 8, column 25: result 0-1 is impossible, cross completed a line"
+        );
+    }
+}
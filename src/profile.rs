@@ -0,0 +1,88 @@
+use std::{fs, io, path::Path};
+
+use bevy::{asset::ron, prelude::*};
+use bevy_replicon::renet::transport::generate_random_bytes;
+use serde::{Deserialize, Serialize};
+
+use crate::components::{AvatarColor, Symbol, DEFAULT_RATING};
+
+/// File the profile is saved to, relative to the working directory.
+const PROFILE_FILE: &str = "profile.ron";
+
+/// Identifies a player across connections and launches, so hosts can remember its rating.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq, Hash)]
+pub struct PlayerId(u64);
+
+impl PlayerId {
+    /// Creates a new random id.
+    pub fn generate() -> Self {
+        Self(u64::from_le_bytes(generate_random_bytes()))
+    }
+}
+
+/// How the local player appears to the others, edited in the profile screen.
+#[derive(Resource, Clone, Debug, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Profile {
+    /// Name shown to the other players, "Host" or "Guest" when empty.
+    pub name: String,
+    /// Symbol played when hosting, the joining player gets the other one.
+    pub preferred_symbol: Symbol,
+    pub avatar_color: AvatarColor,
+    /// Elo rating, updated from the results of the hosted matches.
+    pub rating: i32,
+    /// Generated when the profile is first loaded.
+    pub player_id: Option<PlayerId>,
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            preferred_symbol: Symbol::default(),
+            avatar_color: AvatarColor::default(),
+            rating: DEFAULT_RATING,
+            player_id: None,
+        }
+    }
+}
+
+impl Profile {
+    /// Loads the saved profile, falling back to the default one if the file is invalid.
+    ///
+    /// A profile without a player id gets one, saved right away so it stays the same.
+    pub fn load_or_default() -> Self {
+        let mut profile = Self::load_from(Path::new(PROFILE_FILE))
+            .inspect_err(|e| error!("Failed to load {PROFILE_FILE}: {e}"))
+            .unwrap_or_default();
+        if profile.player_id.is_none() {
+            profile.player_id = Some(PlayerId::generate());
+            if let Err(e) = profile.save() {
+                error!("Failed to save the profile: {e}");
+            }
+        }
+        profile
+    }
+
+    fn load_from(path: &Path) -> anyhow::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(ron::from_str(&text)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        let text = ron::ser::to_string_pretty(self, Default::default())?;
+        fs::write(PROFILE_FILE, text)?;
+        Ok(())
+    }
+
+    /// The name to show, `default` if none was chosen.
+    pub fn name_or<'a>(&'a self, default: &'a str) -> &'a str {
+        match self.name.trim() {
+            "" => default,
+            name => name,
+        }
+    }
+}
@@ -0,0 +1,209 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{
+    components::{CellIndex, Symbol, SymbolBundle},
+    game::{symbol_init_system, winning_line, GameElements, GridNode},
+    notation::NotatedGame,
+    state::GameState,
+    BACKGROUND_COLOR, GRID_SIZE,
+};
+
+/// Playback speeds of the autoplay, in moves per second.
+const SPEEDS: [f32; 4] = [0.5, 1.0, 2.0, 4.0];
+/// Index of the speed a replay starts with.
+const DEFAULT_SPEED: usize = 1;
+
+/// Plays recorded games back on the board.
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        {
+            app.add_systems(
+                Update,
+                (
+                    replay_keys_system,
+                    autoplay_system,
+                    replay_board_system.run_if(resource_exists_and_changed::<Replay>),
+                    highlight_winning_line.after(symbol_init_system),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Replay)),
+            );
+            app.add_systems(OnExit(GameState::Replay), remove_replay);
+        }
+    }
+}
+
+/// The recorded game being replayed, inserted before entering [`GameState::Replay`].
+#[derive(Resource)]
+pub struct Replay {
+    game: NotatedGame,
+    /// Number of moves shown on the board.
+    shown: usize,
+    playing: bool,
+    speed: usize,
+    timer: Timer,
+}
+
+impl Replay {
+    pub fn new(game: NotatedGame) -> Self {
+        Self {
+            game,
+            shown: 0,
+            playing: false,
+            speed: DEFAULT_SPEED,
+            timer: Timer::new(move_interval(DEFAULT_SPEED), TimerMode::Repeating),
+        }
+    }
+
+    pub fn game(&self) -> &NotatedGame {
+        &self.game
+    }
+
+    /// Number of moves shown on the board.
+    pub fn shown(&self) -> usize {
+        self.shown
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    /// Moves per second of the autoplay.
+    pub fn speed(&self) -> f32 {
+        SPEEDS[self.speed]
+    }
+
+    /// Shows the first `moves` moves, the autoplay stops at the end.
+    pub fn go_to(&mut self, moves: usize) {
+        self.shown = moves.min(self.game.moves.len());
+        if self.shown == self.game.moves.len() {
+            self.playing = false;
+        }
+    }
+
+    pub fn previous(&mut self) {
+        self.go_to(self.shown.saturating_sub(1));
+    }
+
+    pub fn next(&mut self) {
+        self.go_to(self.shown + 1);
+    }
+
+    /// Starts or pauses the autoplay, starting over once at the end.
+    pub fn toggle_playing(&mut self) {
+        if self.playing {
+            self.playing = false;
+            return;
+        }
+        if self.shown == self.game.moves.len() {
+            self.shown = 0;
+        }
+        self.playing = true;
+        self.timer.reset();
+    }
+
+    /// Switches to the next playback speed, going back to the slowest after the fastest.
+    pub fn next_speed(&mut self) {
+        self.speed = (self.speed + 1) % SPEEDS.len();
+        self.timer.set_duration(move_interval(self.speed));
+    }
+}
+
+fn move_interval(speed: usize) -> Duration {
+    Duration::from_secs_f32(1.0 / SPEEDS[speed])
+}
+
+fn remove_replay(mut commands: Commands) {
+    commands.remove_resource::<Replay>();
+}
+
+/// Steps with the arrows, jumps with Home and End, and plays or pauses with Space.
+fn replay_keys_system(keyboard_input: Res<ButtonInput<KeyCode>>, mut replay: ResMut<Replay>) {
+    if keyboard_input.just_pressed(KeyCode::ArrowLeft) {
+        replay.previous();
+    }
+    if keyboard_input.just_pressed(KeyCode::ArrowRight) {
+        replay.next();
+    }
+    if keyboard_input.just_pressed(KeyCode::Home) {
+        replay.go_to(0);
+    }
+    if keyboard_input.just_pressed(KeyCode::End) {
+        replay.go_to(usize::MAX);
+    }
+    if keyboard_input.just_pressed(KeyCode::Space) {
+        replay.toggle_playing();
+    }
+}
+
+fn autoplay_system(time: Res<Time>, mut replay: ResMut<Replay>) {
+    if !replay.playing {
+        return;
+    }
+    // Ticking doesn't count as a change, so the board is only updated on moves.
+    let timer = &mut replay.bypass_change_detection().timer;
+    if timer.tick(time.delta()).just_finished() {
+        replay.next();
+    }
+}
+
+/// Puts the symbols of the shown moves on the board, the same way as during a match.
+fn replay_board_system(
+    mut commands: Commands,
+    replay: Res<Replay>,
+    symbols: Query<(Entity, &CellIndex, &Symbol)>,
+) {
+    let shown = &replay.game.moves[..replay.shown];
+    for (symbol_entity, cell_index, &symbol) in &symbols {
+        let played = shown
+            .iter()
+            .position(|&cell| cell == cell_index.index())
+            .is_some_and(|index| Symbol::of_move(index) == symbol);
+        if !played {
+            commands.entity(symbol_entity).despawn_recursive();
+        }
+    }
+    for (index, &cell) in shown.iter().enumerate() {
+        let symbol = Symbol::of_move(index);
+        if !symbols
+            .iter()
+            .any(|(_, cell_index, &s)| cell_index.index() == cell && s == symbol)
+        {
+            commands
+                .spawn(SymbolBundle::new(symbol, cell))
+                .insert(GameElements);
+        }
+    }
+}
+
+/// Colours the line that won the game once its last move is shown, like at the end of a match.
+fn highlight_winning_line(
+    replay: Res<Replay>,
+    grid_nodes: Query<&Children, With<GridNode>>,
+    mut background_colors: Query<&mut BackgroundColor>,
+) {
+    let Ok(buttons) = grid_nodes.get_single() else {
+        return;
+    };
+    let mut board = [None; GRID_SIZE * GRID_SIZE];
+    for (index, &cell) in replay.game.moves[..replay.shown].iter().enumerate() {
+        board[cell] = Some(Symbol::of_move(index));
+    }
+    let line = winning_line(&board);
+
+    for (index, &button) in buttons.iter().enumerate() {
+        let color = match line {
+            Some((symbol, line)) if line.contains(&index) => symbol.color().with_a(0.3),
+            _ => BACKGROUND_COLOR,
+        };
+        if let Ok(mut background) = background_colors.get_mut(button) {
+            if background.0 != color {
+                background.0 = color;
+            }
+        }
+    }
+}
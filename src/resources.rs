@@ -1,9 +1,9 @@
-use std::net::IpAddr;
+use std::net::{SocketAddr, ToSocketAddrs};
 
 use bevy::prelude::*;
 use bevy_replicon::renet::ClientId;
 
-use crate::components::Symbol;
+use crate::{components::Symbol, PORT};
 
 /// Font to display unicode characters for [`Symbol`].
 #[derive(Resource, Deref)]
@@ -29,10 +29,6 @@ impl CurrentTurn {
         self.0
     }
 
-    pub fn next(&mut self) {
-        self.0 = self.0.next();
-    }
-
     pub fn reset(&mut self) {
         self.0 = Symbol::Cross;
     }
@@ -55,15 +51,117 @@ impl Winner {
     }
 }
 
-#[derive(Resource)]
+#[derive(Clone, Resource)]
 pub struct ServerConnectionInfo {
-    pub server_addr: IpAddr,
+    /// IPv4 or IPv6 address, with an optional `%scope`, or host name of the server.
+    pub host: String,
+    /// Game port of the server, as announced by discovery.
+    pub port: u16,
+    pub password: Option<String>,
+}
+
+impl ServerConnectionInfo {
+    /// Resolves the address to connect to, `host` may also include a port like `[::1]:5000` or `example.com:5000`.
+    ///
+    /// Host names are looked up with the system resolver, which blocks.
+    pub fn resolve(&self) -> anyhow::Result<SocketAddr> {
+        if let Ok(addr) = self.host.parse() {
+            return Ok(addr);
+        }
+        let (host, port) = self.host_and_port()?;
+        (host, port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| anyhow::anyhow!("no address found for {}", self.host))
+    }
+
+    /// Splits the port typed after the host from it, [`Self::port`] is used when there is none.
+    ///
+    /// IPv6 addresses need brackets to be followed by a port.
+    fn host_and_port(&self) -> anyhow::Result<(&str, u16)> {
+        let (host, port) = match self.host.strip_prefix('[') {
+            Some(bracketed) => {
+                let (host, rest) = bracketed
+                    .split_once(']')
+                    .ok_or_else(|| anyhow::anyhow!("missing ] in {}", self.host))?;
+                match rest {
+                    "" => (host, None),
+                    rest => {
+                        let port = rest.strip_prefix(':').ok_or_else(|| {
+                            anyhow::anyhow!("unexpected {rest} after the address {host}")
+                        })?;
+                        (host, Some(port))
+                    }
+                }
+            }
+            None => match self.host.split_once(':') {
+                Some((host, port)) if !port.contains(':') => (host, Some(port)),
+                // An IPv6 address without a port.
+                _ => (self.host.as_str(), None),
+            },
+        };
+        let port = match port {
+            Some(port) => port
+                .parse()
+                .map_err(|_| anyhow::anyhow!("invalid port {port} for {host}"))?,
+            None => self.port,
+        };
+        Ok((host, port))
+    }
 }
 
 impl Default for ServerConnectionInfo {
     fn default() -> Self {
         Self {
-            server_addr: IpAddr::V4(std::net::Ipv4Addr::LOCALHOST),
+            host: std::net::Ipv4Addr::LOCALHOST.to_string(),
+            port: PORT,
+            password: None,
         }
     }
-}
\ No newline at end of file
+}
+
+/// Password required to join the hosted match, `None` for a public match.
+#[derive(Resource, Default, Deref, DerefMut)]
+pub struct MatchPassword(Option<String>);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn host_and_port(host: &str) -> anyhow::Result<(String, u16)> {
+        let info = ServerConnectionInfo {
+            host: host.to_string(),
+            ..default()
+        };
+        info.host_and_port()
+            .map(|(host, port)| (host.to_string(), port))
+    }
+
+    #[test]
+    fn typed_ports() {
+        assert_eq!(
+            host_and_port("example.com").unwrap(),
+            ("example.com".to_string(), PORT)
+        );
+        assert_eq!(
+            host_and_port("example.com:5000").unwrap(),
+            ("example.com".to_string(), 5000)
+        );
+        assert_eq!(
+            host_and_port("fe80::1").unwrap(),
+            ("fe80::1".to_string(), PORT)
+        );
+        assert_eq!(
+            host_and_port("[fe80::1]").unwrap(),
+            ("fe80::1".to_string(), PORT)
+        );
+        assert_eq!(
+            host_and_port("[fe80::1]:5000").unwrap(),
+            ("fe80::1".to_string(), 5000)
+        );
+        assert!(host_and_port("example.com:port").is_err());
+        assert!(host_and_port("example.com:70000").is_err());
+        assert!(host_and_port("[fe80::1").is_err());
+        assert!(host_and_port("[fe80::1]5000").is_err());
+    }
+}
@@ -0,0 +1,188 @@
+use std::{fs, io, path::Path, time::Duration};
+
+use bevy::{asset::ron, prelude::*};
+use bevy_replicon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{
+        AvatarColor, CellIndex, Clock, MatchState, MatchStatus, Player, PlayerName, Score, Series,
+        Symbol, SymbolBundle,
+    },
+    game::{GameElements, MoveHistory},
+    state::GameState,
+    GRID_SIZE,
+};
+
+/// File the unfinished hotseat game is saved to, relative to the working directory.
+const SAVE_FILE: &str = "savegame.ron";
+
+/// Saves unfinished hotseat games and resumes them.
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        {
+            app.add_event::<SaveAndQuit>();
+            app.add_systems(
+                OnEnter(GameState::Hotseat),
+                resume_match.run_if(resource_exists::<SavedGame>),
+            );
+            app.add_systems(
+                Update,
+                save_and_quit
+                    .run_if(on_event::<SaveAndQuit>())
+                    .run_if(in_state(GameState::Playing)),
+            );
+        }
+    }
+}
+
+/// Asks to save the hotseat game being played and return to the main menu.
+#[derive(Event)]
+pub struct SaveAndQuit;
+
+/// Marks the moves of a resumed game, they were already played before saving.
+#[derive(Component)]
+pub struct ResumedMove;
+
+/// Every version of the save format, older versions are upgraded when loaded.
+///
+/// Add a variant for each new version instead of changing an existing one.
+#[derive(Deserialize, Serialize)]
+enum SaveFile {
+    V1(SavedGame),
+}
+
+/// How the saved game was played, only hotseat games can be saved for now.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize)]
+pub enum SavedMode {
+    #[default]
+    Hotseat,
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SavedPlayer {
+    pub name: String,
+    pub symbol: Symbol,
+    pub avatar_color: AvatarColor,
+    pub wins: u32,
+    /// Time left on the clock of a timed match.
+    pub clock: Option<Duration>,
+}
+
+/// An unfinished game, inserted as a resource to resume it.
+#[derive(Resource, Clone, Debug, Deserialize, Serialize)]
+pub struct SavedGame {
+    pub mode: SavedMode,
+    pub players: Vec<SavedPlayer>,
+    /// Cells in the order they were played, [`Symbol::Cross`] plays first.
+    pub moves: Vec<usize>,
+    pub series: Option<Series>,
+}
+
+impl SavedGame {
+    /// Whether a game was saved and can be continued.
+    pub fn exists() -> bool {
+        Path::new(SAVE_FILE).exists()
+    }
+
+    /// Loads the saved game and removes it, so it's only continued once.
+    pub fn take() -> anyhow::Result<Self> {
+        let game = Self::load_from(Path::new(SAVE_FILE))?;
+        match fs::remove_file(SAVE_FILE) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(game),
+        }
+    }
+
+    fn load_from(path: &Path) -> anyhow::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        // Older versions will be upgraded to the current one here.
+        let SaveFile::V1(game) = ron::from_str(&text)?;
+        for (index, &cell) in game.moves.iter().enumerate() {
+            anyhow::ensure!(cell < GRID_SIZE * GRID_SIZE, "invalid cell {cell}");
+            anyhow::ensure!(
+                !game.moves[..index].contains(&cell),
+                "cell {cell} played twice"
+            );
+        }
+        Ok(game)
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        let file = SaveFile::V1(self.clone());
+        let text = ron::ser::to_string_pretty(&file, Default::default())?;
+        fs::write(SAVE_FILE, text)?;
+        Ok(())
+    }
+}
+
+/// Whether the hotseat match being played can be saved, online matches can't.
+pub fn can_save(
+    server: Option<Res<RenetServer>>,
+    client: Option<Res<RenetClient>>,
+    match_state: Query<&MatchState>,
+) -> bool {
+    server.is_none()
+        && client.is_none()
+        && match_state
+            .get_single()
+            .is_ok_and(|match_state| match_state.status() == MatchStatus::InProgress)
+}
+
+fn save_and_quit(
+    mut state: ResMut<NextState<GameState>>,
+    history: Res<MoveHistory>,
+    series: Query<&Series>,
+    players: Query<(&PlayerName, &Symbol, &AvatarColor, &Score, Option<&Clock>), With<Player>>,
+    cells: Query<&CellIndex>,
+) {
+    let game = SavedGame {
+        mode: SavedMode::Hotseat,
+        players: players
+            .iter()
+            .map(|(name, &symbol, &avatar_color, score, clock)| SavedPlayer {
+                name: name.name().to_string(),
+                symbol,
+                avatar_color,
+                wins: score.wins(),
+                clock: clock.map(Clock::remaining),
+            })
+            .collect(),
+        moves: history
+            .iter()
+            .filter_map(|&entity| cells.get(entity).ok())
+            .map(CellIndex::index)
+            .collect(),
+        series: series.get_single().ok().copied(),
+    };
+    match game.save() {
+        Ok(()) => info!("saved the game after {} moves", game.moves.len()),
+        Err(e) => error!("Failed to save {SAVE_FILE}: {e}"),
+    }
+    state.set(GameState::MainMenu);
+}
+
+/// Puts back the board and the series of the saved game, its players are seated by the hotseat game.
+fn resume_match(mut commands: Commands, saved_game: Res<SavedGame>) {
+    info!(
+        "resuming a {:?} game after {} moves",
+        saved_game.mode,
+        saved_game.moves.len()
+    );
+    let mut match_state = MatchState::default();
+    match_state.set_moves(saved_game.moves.len());
+    commands
+        .spawn((match_state, Replication))
+        .insert(GameElements);
+    for (index, &cell) in saved_game.moves.iter().enumerate() {
+        commands
+            .spawn(SymbolBundle::new(Symbol::of_move(index), cell))
+            .insert((GameElements, ResumedMove));
+    }
+    if let Some(series) = saved_game.series {
+        commands.spawn((series, Replication)).insert(GameElements);
+    }
+    commands.remove_resource::<SavedGame>();
+}
@@ -4,11 +4,16 @@ use bevy::prelude::*;
 pub enum GameState {
     #[default]
     MainMenu,
+    Profile,
+    Statistics,
+    Replays,
+    Replay,
     Connect,
     HostingLobby,
     WaitingConnection,
     Hotseat,
     Playing,
+    Reconnecting,
     GameOver,
     Draw,
     Disconnected,
@@ -0,0 +1,378 @@
+use std::{collections::BTreeMap, fmt, fs, io, path::Path, time::Duration};
+
+use bevy::{asset::ron, prelude::*};
+use bevy_replicon::{
+    prelude::*,
+    renet::transport::{NetcodeClientTransport, NetcodeServerTransport},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{MatchState, MatchStatus, Player, PlayerName, Rating, Symbol},
+    profile::Profile,
+    state::GameState,
+};
+
+/// File the finished games are recorded to, relative to the working directory.
+const STATS_FILE: &str = "stats.ron";
+
+/// Records the games finished by the local player.
+pub struct StatsPlugin;
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        {
+            app.insert_resource(Statistics::load_or_default());
+            app.init_resource::<GameRecorder>();
+            app.add_systems(Update, (start_recording, save_rating));
+            app.add_systems(OnEnter(GameState::Playing), drop_taken_back_record);
+            for state in [GameState::GameOver, GameState::Draw] {
+                app.add_systems(OnEnter(state), record_finished_game);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Ord, PartialEq, PartialOrd, Serialize)]
+pub enum GameMode {
+    Hotseat,
+    Hosted,
+    Joined,
+}
+
+impl GameMode {
+    /// Mode of the game being played, told by the transport in use.
+    pub fn current(
+        client_transport: Option<&NetcodeClientTransport>,
+        server_transport: Option<&NetcodeServerTransport>,
+    ) -> Self {
+        if client_transport.is_some() {
+            GameMode::Joined
+        } else if server_transport.is_some() {
+            GameMode::Hosted
+        } else {
+            GameMode::Hotseat
+        }
+    }
+}
+
+impl fmt::Display for GameMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GameMode::Hotseat => f.write_str("Hotseat"),
+            GameMode::Hosted => f.write_str("Hosted"),
+            GameMode::Joined => f.write_str("Joined"),
+        }
+    }
+}
+
+/// Outcome of a game for the local player.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub enum GameResult {
+    Win,
+    Loss,
+    Draw,
+}
+
+/// A finished game, as seen by the local player.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GameRecord {
+    pub mode: GameMode,
+    pub opponent: String,
+    pub result: GameResult,
+    pub moves: usize,
+    pub duration: Duration,
+}
+
+/// Wins, losses and draws of a set of games.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Tally {
+    pub wins: usize,
+    pub losses: usize,
+    pub draws: usize,
+}
+
+impl Tally {
+    fn add(&mut self, result: GameResult) {
+        match result {
+            GameResult::Win => self.wins += 1,
+            GameResult::Loss => self.losses += 1,
+            GameResult::Draw => self.draws += 1,
+        }
+    }
+
+    pub fn games(&self) -> usize {
+        self.wins + self.losses + self.draws
+    }
+}
+
+impl fmt::Display for Tally {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} won, {} lost, {} drawn",
+            self.wins, self.losses, self.draws
+        )
+    }
+}
+
+/// Every game finished on this machine, saved to [`STATS_FILE`].
+#[derive(Resource, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Statistics {
+    games: Vec<GameRecord>,
+}
+
+impl Statistics {
+    /// Loads the recorded games, starting over if the file is invalid.
+    pub fn load_or_default() -> Self {
+        Self::load_from(Path::new(STATS_FILE))
+            .inspect_err(|e| error!("Failed to load {STATS_FILE}: {e}"))
+            .unwrap_or_default()
+    }
+
+    fn load_from(path: &Path) -> anyhow::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(text) => Ok(ron::from_str(&text)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        let text = ron::ser::to_string_pretty(self, Default::default())?;
+        fs::write(STATS_FILE, text)?;
+        Ok(())
+    }
+
+    pub fn games(&self) -> &[GameRecord] {
+        &self.games
+    }
+
+    pub fn overall(&self) -> Tally {
+        let mut tally = Tally::default();
+        for game in &self.games {
+            tally.add(game.result);
+        }
+        tally
+    }
+
+    pub fn by_mode(&self) -> BTreeMap<GameMode, Tally> {
+        let mut tallies = BTreeMap::<_, Tally>::new();
+        for game in &self.games {
+            tallies.entry(game.mode).or_default().add(game.result);
+        }
+        tallies
+    }
+
+    /// Head-to-head records, by name of the opponent.
+    pub fn by_opponent(&self) -> BTreeMap<&str, Tally> {
+        let mut tallies = BTreeMap::<_, Tally>::new();
+        for game in &self.games {
+            tallies
+                .entry(game.opponent.as_str())
+                .or_default()
+                .add(game.result);
+        }
+        tallies
+    }
+
+    /// Result of the last games in a row and how many there are.
+    pub fn current_streak(&self) -> Option<(GameResult, usize)> {
+        let last = self.games.last()?.result;
+        let count = self
+            .games
+            .iter()
+            .rev()
+            .take_while(|game| game.result == last)
+            .count();
+        Some((last, count))
+    }
+
+    pub fn longest_win_streak(&self) -> usize {
+        self.games
+            .split(|game| game.result != GameResult::Win)
+            .map(<[_]>::len)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Average number of moves and duration of a game, `None` without games.
+    pub fn average_length(&self) -> Option<(f32, Duration)> {
+        let count = self.games.len();
+        if count == 0 {
+            return None;
+        }
+        let moves: usize = self.games.iter().map(|game| game.moves).sum();
+        let duration: Duration = self.games.iter().map(|game| game.duration).sum();
+        Some((moves as f32 / count as f32, duration / count as u32))
+    }
+}
+
+/// Tracks the match being played, to record it once finished.
+#[derive(Resource, Default)]
+struct GameRecorder {
+    started_at: Duration,
+    /// Match whose end was recorded, a takeback of that end drops the record.
+    recorded: Option<Entity>,
+}
+
+/// Notes when a new match starts, on the server and the clients.
+fn start_recording(
+    time: Res<Time>,
+    mut recorder: ResMut<GameRecorder>,
+    match_states: Query<(), Added<MatchState>>,
+) {
+    if !match_states.is_empty() {
+        recorder.started_at = time.elapsed();
+        recorder.recorded = None;
+    }
+}
+
+/// Records the finished game of the local player, spectators don't record anything.
+///
+/// In hotseat the game is recorded for the first player.
+fn record_finished_game(
+    time: Res<Time>,
+    mut recorder: ResMut<GameRecorder>,
+    mut statistics: ResMut<Statistics>,
+    client_transport: Option<Res<NetcodeClientTransport>>,
+    server_transport: Option<Res<NetcodeServerTransport>>,
+    match_state: Query<(Entity, &MatchState)>,
+    players: Query<(Entity, &Player, &PlayerName, &Symbol)>,
+) {
+    let Ok((match_entity, match_state)) = match_state.get_single() else {
+        return;
+    };
+    let client_id = client_transport
+        .as_ref()
+        .map(|client| client.client_id())
+        .unwrap_or(SERVER_ID);
+    let Some((local_entity, _, _, &local_symbol)) = players
+        .iter()
+        .filter(|(_, player, ..)| player.client_id() == client_id)
+        .min_by_key(|&(entity, ..)| entity)
+    else {
+        return;
+    };
+    let opponent = players
+        .iter()
+        .find(|&(entity, ..)| entity != local_entity)
+        .map(|(_, _, name, _)| name.name().to_string())
+        .unwrap_or_default();
+
+    let result = match match_state.status() {
+        MatchStatus::InProgress => return,
+        MatchStatus::Won { symbol, .. } if symbol == local_symbol => GameResult::Win,
+        MatchStatus::Won { .. } => GameResult::Loss,
+        MatchStatus::Draw => GameResult::Draw,
+    };
+    let mode = GameMode::current(client_transport.as_deref(), server_transport.as_deref());
+
+    info!("recording a {result:?} against {opponent:?}");
+    if recorder.recorded == Some(match_entity) {
+        statistics.games.pop();
+    }
+    statistics.games.push(GameRecord {
+        mode,
+        opponent,
+        result,
+        moves: match_state.move_number(),
+        duration: time.elapsed() - recorder.started_at,
+    });
+    recorder.recorded = Some(match_entity);
+    if let Err(e) = statistics.save() {
+        error!("Failed to save {STATS_FILE}: {e}");
+    }
+}
+
+/// The end of the match was taken back, it will be recorded again when it ends.
+fn drop_taken_back_record(
+    mut recorder: ResMut<GameRecorder>,
+    mut statistics: ResMut<Statistics>,
+    match_state: Query<Entity, With<MatchState>>,
+) {
+    let Ok(match_entity) = match_state.get_single() else {
+        return;
+    };
+    if recorder.recorded == Some(match_entity) {
+        statistics.games.pop();
+        recorder.recorded = None;
+        if let Err(e) = statistics.save() {
+            error!("Failed to save {STATS_FILE}: {e}");
+        }
+    }
+}
+
+/// Saves the rating given by the host to the local player in the profile.
+///
+/// The client never rates itself, so the outcome of the match is always the server's.
+/// Only results and takebacks are saved, the rating a host seats the player with isn't.
+fn save_rating(
+    mut profile: ResMut<Profile>,
+    client_transport: Option<Res<NetcodeClientTransport>>,
+    players: Query<(&Player, Ref<Rating>), Changed<Rating>>,
+) {
+    let client_id = client_transport
+        .map(|client| client.client_id())
+        .unwrap_or(SERVER_ID);
+    for (player, rating) in &players {
+        if rating.is_added() || player.client_id() != client_id || profile.rating == rating.value()
+        {
+            continue;
+        }
+        info!("saving rating {}", rating.value());
+        profile.rating = rating.value();
+        if let Err(e) = profile.save() {
+            error!("Failed to save the profile: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_replicon::renet::ClientId;
+
+    use super::*;
+    use crate::{
+        profile::PlayerId,
+        testing::{client_app_with, host_app, run_until, state},
+    };
+
+    /// Rating of the player seated for `client_id`, as replicated to the app.
+    fn rating_of(app: &mut App, client_id: ClientId) -> Option<i32> {
+        app.world
+            .query::<(&Player, &Rating)>()
+            .iter(&app.world)
+            .find(|(player, _)| player.client_id() == client_id)
+            .map(|(_, rating)| rating.value())
+    }
+
+    #[test]
+    fn guest_keeps_its_rating_when_joining() {
+        let (host, port) = host_app("Alice");
+        let mut guest = client_app_with(
+            Profile {
+                name: "Bob".to_string(),
+                rating: 1500,
+                player_id: Some(PlayerId::generate()),
+                ..default()
+            },
+            port,
+        );
+        guest.add_systems(Update, save_rating);
+        let guest_id = guest.world.resource::<NetcodeClientTransport>().client_id();
+        let mut apps = vec![host, guest];
+        assert!(
+            run_until(&mut apps, |apps| apps.iter_mut().all(|app| state(app)
+                == GameState::Playing
+                && rating_of(app, guest_id).is_some())),
+            "the guest never joined the match"
+        );
+
+        for app in &mut apps {
+            assert_eq!(rating_of(app, guest_id), Some(1500));
+        }
+        assert_eq!(apps[1].world.resource::<Profile>().rating, 1500);
+    }
+}
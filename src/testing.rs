@@ -0,0 +1,192 @@
+//! Headless apps running the real game logic, to test it without a window.
+
+use std::{
+    net::{Ipv4Addr, UdpSocket},
+    thread,
+    time::Duration,
+};
+
+use bevy::{prelude::*, text::FontLoader, time::TimeUpdateStrategy};
+use bevy_replicon::{prelude::*, renet::ClientId};
+
+use crate::{
+    components::{CellIndex, MatchState, Symbol},
+    config::Config,
+    events::CellPick,
+    game::GamePlugin,
+    network::NetworkPlugin,
+    profile::Profile,
+    resources::{CurrentTurn, ServerConnectionInfo, SymbolFont, Winner},
+    save::SavePlugin,
+    state::GameState,
+    GRID_SIZE,
+};
+
+/// Time advanced by each update, so runs don't depend on the speed of the machine.
+pub const FRAME: Duration = Duration::from_millis(16);
+/// Updates it takes for a pick to reach the match state and the game state.
+const SETTLE_UPDATES: usize = 3;
+/// Updates given to apps talking over loopback to reach a condition.
+const MAX_LOOPBACK_UPDATES: usize = 500;
+
+/// An app with the game, save and network plugins and the resources `main` inserts, without rendering.
+///
+/// The configuration and the profile are the default ones, the files of the working directory are ignored.
+pub fn headless_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default()));
+    app.init_asset::<Font>();
+    app.init_asset_loader::<FontLoader>();
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(FRAME));
+    app.add_plugins(NetworkPlugin);
+
+    app.init_state::<GameState>();
+    app.insert_resource(Config::default());
+    app.insert_resource(Profile::default());
+    app.init_resource::<ServerConnectionInfo>();
+    app.init_resource::<CurrentTurn>();
+    app.init_resource::<SymbolFont>();
+    app.insert_resource(Winner::default());
+    app.add_plugins(GamePlugin);
+    app.add_plugins(SavePlugin);
+    app
+}
+
+/// A headless app where both players of a hotseat match are seated and the match started.
+pub fn hotseat_app() -> App {
+    let mut app = headless_app();
+    set_state(&mut app, GameState::Hotseat);
+    assert_eq!(state(&app), GameState::Playing);
+    app
+}
+
+/// Switches to `state` and updates until the transitions it triggers are done.
+pub fn set_state(app: &mut App, state: GameState) {
+    app.world.resource_mut::<NextState<GameState>>().set(state);
+    settle(app);
+}
+
+/// Updates until the changes of the last update went through the game systems and the state transitions.
+pub fn settle(app: &mut App) {
+    for _ in 0..SETTLE_UPDATES {
+        app.update();
+    }
+}
+
+pub fn state(app: &App) -> GameState {
+    *app.world.resource::<State<GameState>>().get()
+}
+
+/// Sends each pick as if it came from `client_id`, letting the turn advance before the next one.
+pub fn play(app: &mut App, picks: &[(ClientId, usize)]) {
+    for &(client_id, index) in picks {
+        app.world.send_event(FromClient {
+            client_id,
+            event: CellPick::new(index),
+        });
+        settle(app);
+    }
+}
+
+/// Symbols on the board, by cell.
+pub fn board(app: &mut App) -> [Option<Symbol>; GRID_SIZE * GRID_SIZE] {
+    let mut board = [None; GRID_SIZE * GRID_SIZE];
+    let mut symbols = app.world.query::<(&CellIndex, &Symbol)>();
+    for (cell_index, &symbol) in symbols.iter(&app.world) {
+        assert!(
+            board[cell_index.index()].replace(symbol).is_none(),
+            "two symbols on cell {}",
+            cell_index.index()
+        );
+    }
+    board
+}
+
+fn free_port() -> u16 {
+    UdpSocket::bind((Ipv4Addr::LOCALHOST, 0))
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// A headless app hosting a match on a free port, returned with the port.
+///
+/// The host doesn't record its matches, tests don't write to the working directory.
+pub fn host_app(name: &str) -> (App, u16) {
+    let port = free_port();
+    let mut app = headless_app();
+    let mut config = app.world.resource_mut::<Config>();
+    config.network.port = port;
+    config.network.record_matches = false;
+    app.world.resource_mut::<Profile>().name = name.to_string();
+    set_state(&mut app, GameState::HostingLobby);
+    assert!(app.world.contains_resource::<RenetServer>());
+    (app, port)
+}
+
+/// A headless app connecting to the host of `port` on loopback.
+pub fn client_app(name: &str, port: u16) -> App {
+    client_app_with(
+        Profile {
+            name: name.to_string(),
+            ..default()
+        },
+        port,
+    )
+}
+
+/// A headless app connecting to the host of `port` on loopback with `profile`.
+pub fn client_app_with(profile: Profile, port: u16) -> App {
+    let mut app = headless_app();
+    app.insert_resource(ServerConnectionInfo {
+        host: Ipv4Addr::LOCALHOST.to_string(),
+        port,
+        password: None,
+    });
+    app.insert_resource(profile);
+    set_state(&mut app, GameState::WaitingConnection);
+    // The address is resolved in the background before the client is created.
+    assert!(run_until(std::slice::from_mut(&mut app), |apps| apps[0]
+        .world
+        .contains_resource::<RenetClient>()));
+    app
+}
+
+/// Updates the apps in lockstep until `condition` holds, `false` if it never does.
+pub fn run_until(apps: &mut [App], condition: impl Fn(&mut [App]) -> bool) -> bool {
+    for _ in 0..MAX_LOOPBACK_UPDATES {
+        for app in apps.iter_mut() {
+            app.update();
+        }
+        if condition(apps) {
+            return true;
+        }
+        // Gives the packets time to cross the loopback.
+        thread::sleep(Duration::from_millis(2));
+    }
+    false
+}
+
+/// Plays each pick from the app at its index, once every app saw the turn of the previous one advance.
+///
+/// Picks are sent like clicks, so the ones of clients go through the network.
+pub fn play_online(apps: &mut [App], picks: &[(usize, usize)]) {
+    for (moves, &(player, index)) in picks.iter().enumerate() {
+        apps[player].world.send_event(CellPick::new(index));
+        assert!(
+            run_until(apps, |apps| apps.iter_mut().all(|app| {
+                move_number(app) == moves + 1 && board(app)[index].is_some()
+            })),
+            "cell {index} picked by app {player} never showed up"
+        );
+    }
+}
+
+/// Moves played in the match replicated to the app, 0 before it starts.
+pub fn move_number(app: &mut App) -> usize {
+    app.world
+        .query::<&MatchState>()
+        .get_single(&app.world)
+        .map_or(0, MatchState::move_number)
+}
@@ -0,0 +1,240 @@
+use bevy::{input::InputSystem, prelude::*};
+use bevy_replicon::prelude::*;
+
+use crate::{
+    events::{ChatMessage, ChatReceived},
+    network::MAX_CHAT_CHARS,
+    state::GameState,
+    ui::text_input::{text_input_bundle, Focused, TextInput, TextInputSubmit},
+    utils::{board_shown, tear_down_with_component},
+    TEXT_COLOR,
+};
+
+use super::{BUTTON_BG_COLOR, HOVER_BG_COLOR};
+
+/// Messages sent with a single click.
+const QUICK_CHAT: [&str; 4] = ["Hello!", "Good luck!", "Well played!", "Good game!"];
+/// Number of messages kept in the chat panel.
+const MAX_CHAT_LINES: usize = 8;
+const CHAT_FONT_SIZE: f32 = 20.0;
+
+pub struct ChatUiPlugin;
+
+impl Plugin for ChatUiPlugin {
+    fn build(&self, app: &mut App) {
+        {
+            app.add_systems(
+                OnEnter(GameState::Playing),
+                setup_chat_ui
+                    .run_if(resource_exists::<RenetServer>.or_else(resource_exists::<RenetClient>)),
+            );
+            // Runs before the game sees Escape, that returns to the main menu.
+            app.add_systems(PreUpdate, close_chat_on_escape.after(InputSystem));
+            app.add_systems(
+                Update,
+                (
+                    open_chat_on_enter,
+                    send_chat_message.run_if(on_event::<TextInputSubmit>()),
+                    handle_quick_chat_buttons,
+                    show_chat_messages.run_if(on_event::<ChatReceived>()),
+                )
+                    .run_if(board_shown),
+            );
+            for state in [
+                GameState::MainMenu,
+                GameState::Reconnecting,
+                GameState::Disconnected,
+            ] {
+                app.add_systems(OnEnter(state), tear_down_with_component::<ChatRoot>);
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+struct ChatRoot;
+
+#[derive(Component)]
+struct ChatLog;
+
+#[derive(Component)]
+struct ChatInput;
+
+#[derive(Component)]
+struct QuickChatButton(&'static str);
+
+fn setup_chat_ui(mut commands: Commands, roots: Query<(), With<ChatRoot>>) {
+    if !roots.is_empty() {
+        return;
+    }
+
+    let text_style = TextStyle {
+        font_size: CHAT_FONT_SIZE,
+        color: TEXT_COLOR,
+        ..default()
+    };
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                right: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                width: Val::Px(320.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(5.0)),
+                ..default()
+            },
+            background_color: Color::GRAY.with_a(0.3).into(),
+            z_index: ZIndex::Global(1),
+            ..default()
+        })
+        .insert(ChatRoot)
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        flex_direction: FlexDirection::Column,
+                        min_height: Val::Px(CHAT_FONT_SIZE * MAX_CHAT_LINES as f32),
+                        justify_content: JustifyContent::FlexEnd,
+                        ..default()
+                    },
+                    ..default()
+                },
+                ChatLog,
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        flex_wrap: FlexWrap::Wrap,
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for text in QUICK_CHAT {
+                        parent
+                            .spawn(ButtonBundle {
+                                style: Style {
+                                    padding: UiRect::all(Val::Px(3.0)),
+                                    ..default()
+                                },
+                                background_color: BUTTON_BG_COLOR.into(),
+                                ..default()
+                            })
+                            .insert(QuickChatButton(text))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(text, text_style.clone()));
+                            });
+                    }
+                });
+            parent.spawn(TextBundle::from_section(
+                "Press Enter to chat",
+                text_style.clone(),
+            ));
+            parent.spawn((
+                text_input_bundle(TextInput::new("").with_max_chars(MAX_CHAT_CHARS), 310.0),
+                ChatInput,
+            ));
+        });
+}
+
+/// Escape leaves the chat instead of the game while typing.
+fn close_chat_on_escape(
+    mut commands: Commands,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    chat_inputs: Query<Entity, (With<ChatInput>, With<Focused>)>,
+) {
+    let Ok(chat_input) = chat_inputs.get_single() else {
+        return;
+    };
+    if keyboard_input.clear_just_pressed(KeyCode::Escape) {
+        commands.entity(chat_input).remove::<Focused>();
+    }
+}
+
+fn open_chat_on_enter(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    chat_inputs: Query<Entity, With<ChatInput>>,
+    focused: Query<(), With<Focused>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::Enter) || !focused.is_empty() {
+        return;
+    }
+    if let Ok(chat_input) = chat_inputs.get_single() {
+        commands.entity(chat_input).insert(Focused);
+    }
+}
+
+/// Sends the typed message when Enter is pressed, and leaves the chat.
+fn send_chat_message(
+    mut commands: Commands,
+    mut submit_events: EventReader<TextInputSubmit>,
+    mut chat_events: EventWriter<ChatMessage>,
+    mut chat_inputs: Query<(Entity, &mut TextInput), (With<ChatInput>, With<Focused>)>,
+) {
+    submit_events.clear();
+    let Ok((chat_input, mut input)) = chat_inputs.get_single_mut() else {
+        return;
+    };
+    if !input.value().trim().is_empty() {
+        chat_events.send(ChatMessage::new(input.value()));
+    }
+    input.set_value("");
+    commands.entity(chat_input).remove::<Focused>();
+}
+
+fn handle_quick_chat_buttons(
+    mut buttons: Query<
+        (&QuickChatButton, &Interaction, &mut BackgroundColor),
+        Changed<Interaction>,
+    >,
+    mut chat_events: EventWriter<ChatMessage>,
+) {
+    for (button, interaction, mut background_color) in &mut buttons {
+        match interaction {
+            Interaction::Pressed => {
+                chat_events.send(ChatMessage::new(button.0));
+            }
+            Interaction::Hovered => *background_color = HOVER_BG_COLOR.into(),
+            Interaction::None => *background_color = BUTTON_BG_COLOR.into(),
+        }
+    }
+}
+
+/// Adds the relayed messages to the chat panel, dropping the oldest ones.
+fn show_chat_messages(
+    mut commands: Commands,
+    mut received_events: EventReader<ChatReceived>,
+    chat_logs: Query<(Entity, Option<&Children>), With<ChatLog>>,
+) {
+    let Ok((chat_log, lines)) = chat_logs.get_single() else {
+        received_events.clear();
+        return;
+    };
+
+    let mut lines: Vec<_> = lines.into_iter().flatten().copied().collect();
+    for event in received_events.read() {
+        let (text, color) = match event.sender() {
+            Some(sender) => (format!("{sender}: {}", event.text()), Color::BLACK),
+            None => (event.text().to_string(), Color::DARK_GRAY),
+        };
+        let line = commands
+            .spawn(TextBundle::from_section(
+                text,
+                TextStyle {
+                    font_size: CHAT_FONT_SIZE,
+                    color,
+                    ..default()
+                },
+            ))
+            .set_parent(chat_log)
+            .id();
+        lines.push(line);
+    }
+
+    let excess = lines.len().saturating_sub(MAX_CHAT_LINES);
+    for &line in &lines[..excess] {
+        commands.entity(line).despawn_recursive();
+    }
+}
@@ -1,13 +1,30 @@
-use bevy::prelude::*;
+use std::net::SocketAddr;
+
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
 
 use crate::{
-    network::{DiscoverServers, DiscoveryClientState, FoundNewServerEvent},
+    network::{
+        ConnectionRejection, DiscoverServers, DiscoveredServer, FoundNewServerEvent,
+        ServerLostEvent,
+    },
     resources::ServerConnectionInfo,
     state::GameState,
-    ui::{BUTTON_BG_COLOR, HOVER_BG_COLOR},
+    ui::{
+        text_input::{text_input_bundle, Focused, TextInput, TextInputSubmit},
+        BUTTON_BG_COLOR, HOVER_BG_COLOR,
+    },
     utils::tear_down_with_component,
+    PORT, PROTOCOL_ID,
 };
 
+/// Height of the visible part of the server list.
+const SERVER_LIST_HEIGHT: f32 = 200.0;
+/// Pixels scrolled per line of the mouse wheel.
+const SCROLL_LINE_HEIGHT: f32 = 20.0;
+
 pub struct ClientUiPlugin;
 
 impl Plugin for ClientUiPlugin {
@@ -17,20 +34,28 @@ impl Plugin for ClientUiPlugin {
             app.add_systems(
                 Update,
                 (
-                    update_server_ip.run_if(resource_changed::<ButtonInput<KeyCode>>),
+                    update_connection_info,
+                    submit_connection_info.run_if(on_event::<TextInputSubmit>()),
                     handle_connect_button,
+                    handle_refresh_button,
+                    handle_server_rows,
+                    scroll_server_list,
                 )
                     .run_if(in_state(GameState::Connect)),
             );
             app.add_systems(
                 Update,
-                handle_new_server_found
-                    .run_if(in_state(GameState::Connect))
-                    .run_if(on_event::<FoundNewServerEvent>()),
+                (
+                    handle_new_server_found.run_if(on_event::<FoundNewServerEvent>()),
+                    handle_server_lost.run_if(on_event::<ServerLostEvent>()),
+                    update_server_rows.run_if(resource_exists_and_changed::<DiscoverServers>),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Connect)),
             );
             app.add_systems(
                 OnExit(GameState::Connect),
-                tear_down_with_component::<ClientConfigRoot>,
+                (tear_down_with_component::<ClientConfigRoot>, stop_discovery),
             );
             app.add_systems(
                 OnEnter(GameState::WaitingConnection),
@@ -40,6 +65,16 @@ impl Plugin for ClientUiPlugin {
                 OnExit(GameState::WaitingConnection),
                 tear_down_with_component::<ClientWaitingRoot>,
             );
+            app.add_systems(OnEnter(GameState::Reconnecting), setup_reconnecting);
+            app.add_systems(
+                OnExit(GameState::Reconnecting),
+                tear_down_with_component::<ClientWaitingRoot>,
+            );
+            app.add_systems(OnEnter(GameState::Disconnected), setup_disconnected);
+            app.add_systems(
+                OnExit(GameState::Disconnected),
+                tear_down_with_component::<ClientWaitingRoot>,
+            );
         }
     }
 }
@@ -53,24 +88,36 @@ struct ClientWaitingRoot;
 #[derive(Component)]
 struct ServerIpTextEdit;
 
+#[derive(Component)]
+struct PasswordTextEdit;
+
 #[derive(Component)]
 struct ConnectButton;
 
-pub fn setup_request_server_ip(
-    mut commands: Commands,
-    connection: Res<ServerConnectionInfo>,
-    mut discovery_client_state: ResMut<NextState<DiscoveryClientState>>,
-) {
+#[derive(Component)]
+struct RefreshButton;
+
+/// The scrolled content of the server list, `position` is its offset from the top.
+#[derive(Component, Default)]
+struct ServerList {
+    position: f32,
+}
+
+/// A line of the server list, clicking on it joins the server.
+///
+/// A server found on several addresses has a single line, joined on the address it was first found on.
+#[derive(Component)]
+struct ServerRow {
+    addr: SocketAddr,
+    server_id: u64,
+}
+
+pub fn setup_request_server_ip(mut commands: Commands, connection: Res<ServerConnectionInfo>) {
     let text_style = TextStyle {
         font_size: 40.0,
         color: Color::BLACK,
         ..default()
     };
-    let input_text_style = TextStyle {
-        font_size: 40.0,
-        color: Color::DARK_GRAY,
-        ..default()
-    };
     let root = commands
         .spawn(NodeBundle {
             style: Style {
@@ -100,29 +147,41 @@ pub fn setup_request_server_ip(
         .id();
     commands
         .spawn(TextBundle {
-            text: Text::from_section("Enter server IP address", text_style.clone()),
+            text: Text::from_section("Enter server address or host name", text_style.clone()),
             ..default()
         })
         .set_parent(container);
+    commands
+        .spawn(text_input_bundle(
+            TextInput::new(connection.host.clone()).with_filter(|c| {
+                c.is_ascii_alphanumeric() || matches!(c, '.' | ':' | '-' | '_' | '%' | '[' | ']')
+            }),
+            400.0,
+        ))
+        .insert((ServerIpTextEdit, Focused))
+        .set_parent(container);
     commands
         .spawn(TextBundle {
-            text: Text::from_section(connection.server_addr.to_string(), input_text_style),
+            text: Text::from_section("Password (if any)", text_style.clone()),
             style: Style {
-                width: Val::Px(300.0),
-                align_items: AlignItems::Center,
-                justify_content: JustifyContent::Center,
+                margin: UiRect::top(Val::Px(20.0)),
                 ..default()
             },
-            background_color: Color::GRAY.into(),
             ..default()
         })
-        .insert(ServerIpTextEdit)
+        .set_parent(container);
+    commands
+        .spawn(text_input_bundle(
+            TextInput::new(connection.password.clone().unwrap_or_default()).masked(),
+            400.0,
+        ))
+        .insert(PasswordTextEdit)
         .set_parent(container);
 
     commands
         .spawn(ButtonBundle {
             style: Style {
-                margin: UiRect::top(Val::Px(50.0)),
+                margin: UiRect::top(Val::Px(30.0)),
                 ..default()
             },
             ..default()
@@ -130,7 +189,7 @@ pub fn setup_request_server_ip(
         .insert(ConnectButton)
         .with_children(|parent| {
             parent.spawn(TextBundle {
-                text: Text::from_section("Connect", text_style),
+                text: Text::from_section("Connect", text_style.clone()),
                 style: Style {
                     margin: UiRect::all(Val::Px(10.0)),
                     ..default()
@@ -139,79 +198,314 @@ pub fn setup_request_server_ip(
             });
         })
         .set_parent(container);
+
+    let header = commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(700.0),
+                margin: UiRect::top(Val::Px(30.0)),
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::SpaceBetween,
+                ..default()
+            },
+            ..default()
+        })
+        .set_parent(container)
+        .id();
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section("Games on the local network", text_style.clone()),
+            ..default()
+        })
+        .set_parent(header);
+    commands
+        .spawn(ButtonBundle::default())
+        .insert(RefreshButton)
+        .with_children(|parent| {
+            parent.spawn(TextBundle {
+                text: Text::from_section("Refresh", text_style),
+                style: Style {
+                    margin: UiRect::all(Val::Px(5.0)),
+                    ..default()
+                },
+                ..default()
+            });
+        })
+        .set_parent(header);
+    // Only the part of the list inside this node is visible, the list moves inside it when scrolled.
+    let list_viewport = commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Px(700.0),
+                height: Val::Px(SERVER_LIST_HEIGHT),
+                overflow: Overflow::clip_y(),
+                ..default()
+            },
+            background_color: Color::rgb(0.8, 0.8, 0.8).into(),
+            ..default()
+        })
+        .set_parent(container)
+        .id();
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_self: AlignSelf::Start,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(ServerList::default())
+        .set_parent(list_viewport);
     // Start the discovery client
-    discovery_client_state.set(DiscoveryClientState::Running);
+    commands.insert_resource(DiscoverServers::default());
 }
 
-fn update_server_ip(
-    mut query: Query<(&ServerIpTextEdit, &mut Text)>,
-    mut state: ResMut<NextState<GameState>>,
+fn update_connection_info(
+    ip_inputs: Query<&TextInput, (With<ServerIpTextEdit>, Changed<TextInput>)>,
+    password_inputs: Query<&TextInput, (With<PasswordTextEdit>, Changed<TextInput>)>,
     mut connection: ResMut<ServerConnectionInfo>,
-    input: Res<ButtonInput<KeyCode>>,
 ) {
-    const VALID_KEYS: [(KeyCode, char); 22] = [
-        (KeyCode::Digit0, '0'),
-        (KeyCode::Digit1, '1'),
-        (KeyCode::Digit2, '2'),
-        (KeyCode::Digit3, '3'),
-        (KeyCode::Digit4, '4'),
-        (KeyCode::Digit5, '5'),
-        (KeyCode::Digit6, '6'),
-        (KeyCode::Digit7, '7'),
-        (KeyCode::Digit8, '8'),
-        (KeyCode::Digit9, '9'),
-        (KeyCode::Period, '.'),
-        (KeyCode::NumpadDecimal, '.'),
-        (KeyCode::Numpad0, '0'),
-        (KeyCode::Numpad1, '1'),
-        (KeyCode::Numpad2, '2'),
-        (KeyCode::Numpad3, '3'),
-        (KeyCode::Numpad4, '4'),
-        (KeyCode::Numpad5, '5'),
-        (KeyCode::Numpad6, '6'),
-        (KeyCode::Numpad7, '7'),
-        (KeyCode::Numpad8, '8'),
-        (KeyCode::Numpad9, '9'),
-    ];
-    for (_, mut text) in query.iter_mut() {
-        let mut current_input_text = text.sections[0].value.clone();
-        if input.just_pressed(KeyCode::Backspace) {
-            current_input_text.pop();
+    for input in &ip_inputs {
+        if input.value() != connection.host {
+            connection.host = input.value().to_string();
+            // A typed address is reached on the default port.
+            connection.port = PORT;
+        }
+    }
+    for input in &password_inputs {
+        connection.password =
+            Some(input.value().to_string()).filter(|password| !password.is_empty());
+    }
+}
+
+/// Stops the discovery client, the list of servers is only shown on the connect screen.
+fn stop_discovery(mut commands: Commands) {
+    commands.remove_resource::<DiscoverServers>();
+}
+
+/// Pressing Enter in any of the fields connects.
+fn submit_connection_info(
+    mut submit_events: EventReader<TextInputSubmit>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    submit_events.clear();
+    state.set(GameState::WaitingConnection);
+}
+
+/// Adds a line to the server list for each new server.
+fn handle_new_server_found(
+    mut commands: Commands,
+    mut event: EventReader<FoundNewServerEvent>,
+    server_list: Query<Entity, With<ServerList>>,
+    rows: Query<&ServerRow>,
+) {
+    let Ok(server_list) = server_list.get_single() else {
+        return;
+    };
+    let mut listed: Vec<_> = rows.iter().map(|row| row.server_id).collect();
+    for event in event.read() {
+        let server_id = event.server.payload.server_id;
+        if listed.contains(&server_id) {
+            continue;
         }
-        for key in input.get_just_pressed() {
-            if let Some((_, key)) = VALID_KEYS.iter().find(|(k, _)| k == key) {
-                current_input_text.push_str(&key.to_string());
+        listed.push(server_id);
+        commands
+            .spawn(ButtonBundle {
+                style: Style {
+                    padding: UiRect::all(Val::Px(5.0)),
+                    ..default()
+                },
+                ..default()
+            })
+            .insert(ServerRow {
+                addr: event.server.addr,
+                server_id,
+            })
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    server_row_text(&event.server),
+                    TextStyle {
+                        font_size: 24.0,
+                        color: Color::BLACK,
+                        ..default()
+                    },
+                ));
+            })
+            .set_parent(server_list);
+    }
+}
+
+/// Removes the line of the servers that stopped answering, unless they still answer on another address.
+fn handle_server_lost(
+    mut commands: Commands,
+    mut event: EventReader<ServerLostEvent>,
+    servers: Res<DiscoverServers>,
+    mut rows: Query<(Entity, &mut ServerRow)>,
+) {
+    for event in event.read() {
+        for (entity, mut row) in rows.iter_mut().filter(|(_, row)| row.addr == event.addr) {
+            let server_id = row.server_id;
+            match servers
+                .servers()
+                .find(|server| server.payload.server_id == server_id)
+            {
+                Some(server) => row.addr = server.addr,
+                None => commands.entity(entity).despawn_recursive(),
             }
         }
-        if let Ok(ip) = current_input_text.parse() {
-            connection.server_addr = ip;
+    }
+}
+
+/// Refreshes the ping and occupancy shown on the lines of the server list.
+fn update_server_rows(
+    servers: Res<DiscoverServers>,
+    rows: Query<(&ServerRow, &Children)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (row, children) in &rows {
+        let Some(server) = servers.get(row.addr) else {
+            continue;
+        };
+        let value = server_row_text(server);
+        let mut texts = texts.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            if text.sections[0].value != value {
+                text.sections[0].value = value.clone();
+            }
         }
-        text.sections[0].value = current_input_text;
-        if input.just_pressed(KeyCode::Enter) {
-            state.set(GameState::WaitingConnection);
+    }
+}
+
+/// The host to connect to a discovered server, link-local IPv6 addresses need the scope.
+fn host_of(addr: SocketAddr) -> String {
+    match addr {
+        SocketAddr::V6(addr) if addr.scope_id() != 0 => {
+            format!("{}%{}", addr.ip(), addr.scope_id())
         }
+        _ => addr.ip().to_string(),
     }
 }
 
-fn handle_new_server_found(
-    mut query: Query<(&ServerIpTextEdit, &mut Text)>,
-    mut event: EventReader<FoundNewServerEvent>,
+fn server_row_text(server: &DiscoveredServer) -> String {
+    let info = &server.payload;
+    let mut text = format!(
+        "{} ({}) @ {} - {} {}x{} - {}/{} players - {} ms",
+        info.host_name,
+        info.host_rating,
+        host_of(server.addr),
+        info.game_mode,
+        info.board_size,
+        info.board_size,
+        info.seats_total.saturating_sub(info.seats_free),
+        info.seats_total,
+        server.ping.as_millis(),
+    );
+    if info.password {
+        text.push_str(" - password");
+    }
+    if !is_compatible(server) {
+        text.push_str(" - incompatible version");
+    }
+    text
+}
+
+/// Whether we can play with this server.
+fn is_compatible(server: &DiscoveredServer) -> bool {
+    server.payload.protocol_id == PROTOCOL_ID
+        && server.payload.build_version == env!("CARGO_PKG_VERSION")
+}
+
+/// Joins the server of the clicked line.
+///
+/// If the server needs a password that wasn't typed yet, it's selected and the password field focused.
+fn handle_server_rows(
+    mut commands: Commands,
+    mut rows: Query<(&ServerRow, &Interaction, &mut BackgroundColor), Changed<Interaction>>,
+    mut ip_inputs: Query<&mut TextInput, With<ServerIpTextEdit>>,
+    password_inputs: Query<Entity, With<PasswordTextEdit>>,
+    focused: Query<Entity, With<Focused>>,
+    servers: Res<DiscoverServers>,
     mut connection: ResMut<ServerConnectionInfo>,
-    _servers: Res<DiscoverServers>,
+    mut state: ResMut<NextState<GameState>>,
 ) {
-    // If we want to use a text input to select the server
-    for event in event.read() {
-        for (_, mut text) in query.iter_mut() {
-            let new_ip = event.server.to_string();
-            text.sections[0].value = new_ip;
-            connection.server_addr = event.server;
+    for (row, interaction, mut background_color) in &mut rows {
+        match *interaction {
+            Interaction::Pressed => {
+                let Some(server) = servers.get(row.addr).filter(|server| is_compatible(server))
+                else {
+                    continue;
+                };
+                connection.host = host_of(server.addr);
+                connection.port = server.payload.game_port;
+                if server.payload.password && connection.password.is_none() {
+                    for mut input in &mut ip_inputs {
+                        input.set_value(connection.host.clone());
+                    }
+                    for entity in &focused {
+                        commands.entity(entity).remove::<Focused>();
+                    }
+                    for entity in &password_inputs {
+                        commands.entity(entity).insert(Focused);
+                    }
+                    continue;
+                }
+                state.set(GameState::WaitingConnection);
+            }
+            Interaction::Hovered => {
+                *background_color = HOVER_BG_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = BUTTON_BG_COLOR.into();
+            }
+        }
+    }
+}
+
+/// Clears the server list, the servers still running answer the next probe.
+fn handle_refresh_button(
+    mut commands: Commands,
+    mut refresh_button: Query<
+        (&Interaction, &mut BackgroundColor),
+        (Changed<Interaction>, With<RefreshButton>),
+    >,
+    rows: Query<Entity, With<ServerRow>>,
+    mut servers: ResMut<DiscoverServers>,
+) {
+    for (interaction, mut background_color) in &mut refresh_button {
+        match *interaction {
+            Interaction::Pressed => {
+                servers.clear();
+                for entity in &rows {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            Interaction::Hovered => {
+                *background_color = HOVER_BG_COLOR.into();
+            }
+            Interaction::None => {
+                *background_color = BUTTON_BG_COLOR.into();
+            }
         }
     }
+}
 
-    // If we want to use a drop down menu to select the server
-    // for server in servers.servers() {
-    //     info!("Found server: {}", server);
-    // }
+fn scroll_server_list(
+    mut mouse_wheel_events: EventReader<MouseWheel>,
+    mut lists: Query<(&mut ServerList, &mut Style, &Node)>,
+) {
+    for event in mouse_wheel_events.read() {
+        for (mut list, mut style, node) in &mut lists {
+            let max_scroll = (node.size().y - SERVER_LIST_HEIGHT).max(0.0);
+            let dy = match event.unit {
+                MouseScrollUnit::Line => event.y * SCROLL_LINE_HEIGHT,
+                MouseScrollUnit::Pixel => event.y,
+            };
+            list.position = (list.position + dy).clamp(-max_scroll, 0.0);
+            style.top = Val::Px(list.position);
+        }
+    }
 }
 
 fn handle_connect_button(
@@ -220,15 +514,12 @@ fn handle_connect_button(
         Changed<Interaction>,
     >,
     mut state: ResMut<NextState<GameState>>,
-    mut discover_client_state: ResMut<NextState<DiscoveryClientState>>,
 ) {
     for (_, interaction, mut background_color) in connect_button.iter_mut() {
         match *interaction {
             Interaction::Pressed => {
                 *background_color = HOVER_BG_COLOR.into();
                 state.set(GameState::WaitingConnection);
-                // Stop the discovery client
-                discover_client_state.set(DiscoveryClientState::Stopped);
             }
             Interaction::Hovered => {
                 *background_color = HOVER_BG_COLOR.into();
@@ -241,6 +532,35 @@ fn handle_connect_button(
 }
 
 fn setup_waiting_connection(mut commands: Commands, connection: Res<ServerConnectionInfo>) {
+    spawn_status_screen(
+        &mut commands,
+        format!(
+            "Connecting to server @ {} port {}",
+            connection.host, connection.port
+        ),
+    );
+}
+
+fn setup_reconnecting(mut commands: Commands) {
+    spawn_status_screen(
+        &mut commands,
+        "Connection lost, trying to reconnect...".to_string(),
+    );
+}
+
+fn setup_disconnected(mut commands: Commands, rejection: Option<Res<ConnectionRejection>>) {
+    let message = match rejection {
+        Some(reason) => format!("Connection rejected. {}", **reason),
+        None => "Disconnected.".to_string(),
+    };
+    spawn_status_screen(
+        &mut commands,
+        format!("{message}\nPress 'ESC' to return to the main menu."),
+    );
+}
+
+/// Spawns a screen with a single centered message.
+fn spawn_status_screen(commands: &mut Commands, message: String) {
     let text_style = TextStyle {
         font_size: 40.0,
         color: Color::BLACK,
@@ -276,10 +596,7 @@ fn setup_waiting_connection(mut commands: Commands, connection: Res<ServerConnec
         .id();
     commands
         .spawn(TextBundle {
-            text: Text::from_section(
-                format!("Connecting to server @ {}", connection.server_addr),
-                text_style,
-            ),
+            text: Text::from_section(message, text_style),
             ..default()
         })
         .set_parent(container);
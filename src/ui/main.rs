@@ -1,6 +1,6 @@
 use bevy::{app::AppExit, ecs::query::QueryData, prelude::*};
 
-use crate::{state::GameState, utils::tear_down_with_component};
+use crate::{save::SavedGame, state::GameState, utils::tear_down_with_component};
 
 use super::{BUTTON_BG_COLOR, HOVER_BG_COLOR};
 
@@ -27,9 +27,13 @@ struct MainMenuRoot;
 
 #[derive(Component)]
 enum MenuButton {
+    Continue,
     Hotseat,
     Host,
     Join,
+    Profile,
+    Statistics,
+    Replays,
     Quit,
 }
 
@@ -78,12 +82,19 @@ pub fn setup_main_menu(mut commands: Commands) {
         .set_parent(root)
         .id();
 
-    let buttons = vec![
+    let mut buttons = Vec::new();
+    if SavedGame::exists() {
+        buttons.push(("Continue", MenuButton::Continue));
+    }
+    buttons.extend([
         ("Hotseat", MenuButton::Hotseat),
         ("Host", MenuButton::Host),
         ("Join", MenuButton::Join),
+        ("Profile", MenuButton::Profile),
+        ("Statistics", MenuButton::Statistics),
+        ("Replays", MenuButton::Replays),
         ("Quit", MenuButton::Quit),
-    ];
+    ]);
 
     for (text, button) in buttons {
         commands
@@ -112,6 +123,7 @@ struct HandleButtonQuery {
 }
 
 fn handle_main_menu_buttons(
+    mut commands: Commands,
     mut state: ResMut<NextState<GameState>>,
     mut interaction_query: Query<HandleButtonQuery, Changed<Interaction>>,
     mut app_exit_events: EventWriter<AppExit>,
@@ -119,6 +131,13 @@ fn handle_main_menu_buttons(
     for mut query in interaction_query.iter_mut() {
         match query.interaction {
             Interaction::Pressed => match query.button {
+                MenuButton::Continue => match SavedGame::take() {
+                    Ok(saved_game) => {
+                        commands.insert_resource(saved_game);
+                        state.set(GameState::Hotseat);
+                    }
+                    Err(e) => error!("Failed to load the saved game: {e}"),
+                },
                 MenuButton::Hotseat => {
                     state.set(GameState::Hotseat);
                 }
@@ -128,6 +147,15 @@ fn handle_main_menu_buttons(
                 MenuButton::Join => {
                     state.set(GameState::Connect);
                 }
+                MenuButton::Profile => {
+                    state.set(GameState::Profile);
+                }
+                MenuButton::Statistics => {
+                    state.set(GameState::Statistics);
+                }
+                MenuButton::Replays => {
+                    state.set(GameState::Replays);
+                }
                 MenuButton::Quit => {
                     app_exit_events.send(AppExit);
                 }
@@ -1,6 +1,14 @@
+mod chat;
 mod client;
 mod main;
+mod profile;
+mod quit;
+mod replay;
 mod server;
+mod stats;
+mod takeback;
+mod text_input;
+mod toast;
 mod turn;
 mod winner;
 
@@ -19,9 +27,17 @@ pub struct MenuPlugin;
 impl PluginGroup for MenuPlugin {
     fn build(self) -> PluginGroupBuilder {
         PluginGroupBuilder::start::<Self>()
+            .add(chat::ChatUiPlugin)
             .add(client::ClientUiPlugin)
             .add(main::MainMenuPlugin)
+            .add(profile::ProfileUiPlugin)
+            .add(quit::QuitUiPlugin)
+            .add(replay::ReplayUiPlugin)
             .add(server::ServerUiPlugin)
+            .add(stats::StatsUiPlugin)
+            .add(takeback::TakebackUiPlugin)
+            .add(text_input::TextInputPlugin)
+            .add(toast::ToastUiPlugin)
             .add(turn::TurnUiPlugin)
             .add(winner::WinnerPlugin)
     }
@@ -0,0 +1,265 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{AvatarColor, Symbol},
+    profile::Profile,
+    resources::SymbolFont,
+    state::GameState,
+    ui::text_input::{text_input_bundle, Focused, TextInput, TextInputSubmit},
+    utils::tear_down_with_component,
+};
+
+use super::{BUTTON_BG_COLOR, HOVER_BG_COLOR};
+
+/// Longest name that can be chosen, in characters.
+const MAX_NAME_CHARS: usize = 16;
+const SELECTED_BG_COLOR: Color = Color::rgba(0.5, 0.5, 0.5, 0.8);
+
+pub struct ProfileUiPlugin;
+
+impl Plugin for ProfileUiPlugin {
+    fn build(&self, app: &mut App) {
+        {
+            app.add_systems(OnEnter(GameState::Profile), setup_profile_screen);
+            app.add_systems(
+                Update,
+                (
+                    handle_symbol_buttons,
+                    handle_color_buttons,
+                    (
+                        handle_save_button,
+                        save_profile.run_if(on_event::<TextInputSubmit>()),
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Profile)),
+            );
+            app.add_systems(
+                OnExit(GameState::Profile),
+                (
+                    tear_down_with_component::<ProfileRoot>,
+                    remove_profile_draft,
+                ),
+            );
+        }
+    }
+}
+
+#[derive(Component)]
+struct ProfileRoot;
+
+#[derive(Component)]
+struct NameTextEdit;
+
+#[derive(Component)]
+struct SymbolButton(Symbol);
+
+#[derive(Component)]
+struct ColorButton(AvatarColor);
+
+#[derive(Component)]
+struct SaveButton;
+
+/// The profile being edited, saved only when leaving with the Save button or Enter.
+#[derive(Resource, Deref, DerefMut)]
+struct ProfileDraft(Profile);
+
+fn setup_profile_screen(
+    mut commands: Commands,
+    profile: Res<Profile>,
+    symbol_font: Res<SymbolFont>,
+) {
+    commands.insert_resource(ProfileDraft(profile.clone()));
+
+    let text_style = TextStyle {
+        font_size: 40.0,
+        color: Color::BLACK,
+        ..default()
+    };
+    let label_style = Style {
+        margin: UiRect::top(Val::Px(30.0)),
+        ..default()
+    };
+    let root = commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert((ProfileRoot, Name::new("UIRoot")))
+        .id();
+
+    commands
+        .spawn(TextBundle::from_section("Name", text_style.clone()))
+        .set_parent(root);
+    commands
+        .spawn(text_input_bundle(
+            TextInput::new(profile.name.clone()).with_max_chars(MAX_NAME_CHARS),
+            400.0,
+        ))
+        .insert((NameTextEdit, Focused))
+        .set_parent(root);
+
+    commands
+        .spawn(
+            TextBundle::from_section("Preferred symbol", text_style.clone())
+                .with_style(label_style.clone()),
+        )
+        .set_parent(root);
+    let symbols = commands.spawn(NodeBundle::default()).set_parent(root).id();
+    for symbol in [Symbol::Cross, Symbol::Nought] {
+        commands
+            .spawn(ButtonBundle {
+                style: Style {
+                    margin: UiRect::all(Val::Px(5.0)),
+                    padding: UiRect::all(Val::Px(5.0)),
+                    ..default()
+                },
+                background_color: BUTTON_BG_COLOR.into(),
+                ..default()
+            })
+            .insert(SymbolButton(symbol))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    symbol.glyph(),
+                    TextStyle {
+                        font: symbol_font.clone(),
+                        font_size: 40.0,
+                        color: symbol.color(),
+                    },
+                ));
+            })
+            .set_parent(symbols);
+    }
+
+    commands
+        .spawn(
+            TextBundle::from_section("Avatar colour", text_style.clone()).with_style(label_style),
+        )
+        .set_parent(root);
+    let colors = commands.spawn(NodeBundle::default()).set_parent(root).id();
+    for avatar_color in AvatarColor::ALL {
+        commands
+            .spawn(ButtonBundle {
+                style: Style {
+                    width: Val::Px(40.0),
+                    height: Val::Px(40.0),
+                    margin: UiRect::all(Val::Px(5.0)),
+                    border: UiRect::all(Val::Px(4.0)),
+                    ..default()
+                },
+                background_color: avatar_color.color().into(),
+                ..default()
+            })
+            .insert(ColorButton(avatar_color))
+            .set_parent(colors);
+    }
+
+    commands
+        .spawn(ButtonBundle {
+            style: Style {
+                margin: UiRect::top(Val::Px(30.0)),
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            background_color: BUTTON_BG_COLOR.into(),
+            ..default()
+        })
+        .insert(SaveButton)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Save", text_style));
+        })
+        .set_parent(root);
+}
+
+fn remove_profile_draft(mut commands: Commands) {
+    commands.remove_resource::<ProfileDraft>();
+}
+
+/// Picks the preferred symbol, the selected one is highlighted.
+fn handle_symbol_buttons(
+    mut draft: ResMut<ProfileDraft>,
+    mut buttons: Query<(&SymbolButton, &Interaction, &mut BackgroundColor)>,
+) {
+    for (button, interaction, mut background_color) in &mut buttons {
+        if *interaction == Interaction::Pressed {
+            draft.preferred_symbol = button.0;
+        }
+        let color = if draft.preferred_symbol == button.0 {
+            SELECTED_BG_COLOR
+        } else if *interaction == Interaction::Hovered {
+            HOVER_BG_COLOR
+        } else {
+            BUTTON_BG_COLOR
+        };
+        if background_color.0 != color {
+            background_color.0 = color;
+        }
+    }
+}
+
+/// Picks the avatar colour, the selected one has a border.
+fn handle_color_buttons(
+    mut draft: ResMut<ProfileDraft>,
+    mut buttons: Query<(&ColorButton, &Interaction, &mut BorderColor)>,
+) {
+    for (button, interaction, mut border_color) in &mut buttons {
+        if *interaction == Interaction::Pressed {
+            draft.avatar_color = button.0;
+        }
+        let color = if draft.avatar_color == button.0 {
+            Color::BLACK
+        } else if *interaction == Interaction::Hovered {
+            HOVER_BG_COLOR
+        } else {
+            Color::NONE
+        };
+        if border_color.0 != color {
+            border_color.0 = color;
+        }
+    }
+}
+
+fn handle_save_button(
+    mut buttons: Query<
+        (&Interaction, &mut BackgroundColor),
+        (With<SaveButton>, Changed<Interaction>),
+    >,
+    mut submit_events: EventWriter<TextInputSubmit>,
+) {
+    for (interaction, mut background_color) in &mut buttons {
+        match interaction {
+            Interaction::Pressed => {
+                submit_events.send(TextInputSubmit);
+            }
+            Interaction::Hovered => *background_color = HOVER_BG_COLOR.into(),
+            Interaction::None => *background_color = BUTTON_BG_COLOR.into(),
+        }
+    }
+}
+
+/// Saves the profile and goes back to the main menu.
+fn save_profile(
+    mut commands: Commands,
+    mut submit_events: EventReader<TextInputSubmit>,
+    mut draft: ResMut<ProfileDraft>,
+    name_inputs: Query<&TextInput, With<NameTextEdit>>,
+    mut state: ResMut<NextState<GameState>>,
+) {
+    submit_events.clear();
+    if let Ok(input) = name_inputs.get_single() {
+        draft.name = input.value().trim().to_string();
+    }
+    if let Err(e) = draft.save() {
+        error!("Failed to save the profile: {e}");
+    }
+    info!("profile saved as {:?}", draft.name);
+    commands.insert_resource(draft.0.clone());
+    state.set(GameState::MainMenu);
+}
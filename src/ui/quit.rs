@@ -0,0 +1,135 @@
+use bevy::{input::InputSystem, prelude::*, ui::FocusPolicy};
+
+use crate::{
+    save::{can_save, SaveAndQuit},
+    state::GameState,
+    ui::{BUTTON_BG_COLOR, FONT_SIZE, HOVER_BG_COLOR},
+    utils::tear_down_with_component,
+    TEXT_COLOR,
+};
+
+pub struct QuitUiPlugin;
+
+impl Plugin for QuitUiPlugin {
+    fn build(&self, app: &mut App) {
+        {
+            // Runs before the game sees Escape, that returns to the main menu without saving.
+            app.add_systems(
+                PreUpdate,
+                toggle_quit_dialog
+                    .after(InputSystem)
+                    .run_if(in_state(GameState::Playing))
+                    .run_if(can_save),
+            );
+            app.add_systems(
+                Update,
+                handle_quit_buttons.run_if(in_state(GameState::Playing)),
+            );
+            app.add_systems(
+                OnExit(GameState::Playing),
+                tear_down_with_component::<QuitDialogRoot>,
+            );
+        }
+    }
+}
+
+#[derive(Component)]
+struct QuitDialogRoot;
+
+#[derive(Clone, Component, Copy)]
+enum QuitButton {
+    SaveAndQuit,
+    Quit,
+    KeepPlaying,
+}
+
+/// Escape offers to save an unfinished hotseat match before leaving it, or closes the offer.
+fn toggle_quit_dialog(
+    mut commands: Commands,
+    mut keyboard_input: ResMut<ButtonInput<KeyCode>>,
+    roots: Query<Entity, With<QuitDialogRoot>>,
+) {
+    if !keyboard_input.clear_just_pressed(KeyCode::Escape) {
+        return;
+    }
+    if let Ok(root) = roots.get_single() {
+        commands.entity(root).despawn_recursive();
+        return;
+    }
+
+    let text_style = TextStyle {
+        font_size: FONT_SIZE,
+        color: TEXT_COLOR,
+        ..default()
+    };
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            background_color: Color::BLACK.with_a(0.6).into(),
+            // Keeps the board from being played behind the dialog.
+            focus_policy: FocusPolicy::Block,
+            z_index: ZIndex::Global(2),
+            ..default()
+        })
+        .insert(QuitDialogRoot)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Leave the match?",
+                text_style.clone(),
+            ));
+            for (text, button) in [
+                ("Save and quit", QuitButton::SaveAndQuit),
+                ("Quit without saving", QuitButton::Quit),
+                ("Keep playing", QuitButton::KeepPlaying),
+            ] {
+                parent
+                    .spawn(ButtonBundle {
+                        style: Style {
+                            margin: UiRect::top(Val::Px(10.0)),
+                            padding: UiRect::all(Val::Px(5.0)),
+                            ..default()
+                        },
+                        background_color: BUTTON_BG_COLOR.into(),
+                        ..default()
+                    })
+                    .insert(button)
+                    .with_children(|parent| {
+                        parent.spawn(TextBundle::from_section(text, text_style.clone()));
+                    });
+            }
+        });
+}
+
+fn handle_quit_buttons(
+    mut commands: Commands,
+    mut state: ResMut<NextState<GameState>>,
+    mut save_events: EventWriter<SaveAndQuit>,
+    mut buttons: Query<(&QuitButton, &Interaction, &mut BackgroundColor), Changed<Interaction>>,
+    roots: Query<Entity, With<QuitDialogRoot>>,
+) {
+    for (&button, interaction, mut background_color) in &mut buttons {
+        match interaction {
+            Interaction::Pressed => match button {
+                QuitButton::SaveAndQuit => {
+                    save_events.send(SaveAndQuit);
+                }
+                QuitButton::Quit => state.set(GameState::MainMenu),
+                QuitButton::KeepPlaying => {
+                    for root in &roots {
+                        commands.entity(root).despawn_recursive();
+                    }
+                }
+            },
+            Interaction::Hovered => *background_color = HOVER_BG_COLOR.into(),
+            Interaction::None => *background_color = BUTTON_BG_COLOR.into(),
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+use crate::{
+    notation::{square, NotatedGame},
+    replay::Replay,
+    state::GameState,
+    ui::{BUTTON_BG_COLOR, HOVER_BG_COLOR},
+    utils::{any_component_changed, tear_down_with_component},
+    TEXT_COLOR,
+};
+
+/// Records listed in the Replays screen.
+const MAX_LISTED_RECORDS: usize = 10;
+const REPLAY_FONT_SIZE: f32 = 25.0;
+const CURRENT_MOVE_BG_COLOR: Color = Color::rgba(0.5, 0.5, 0.5, 0.8);
+
+pub struct ReplayUiPlugin;
+
+impl Plugin for ReplayUiPlugin {
+    fn build(&self, app: &mut App) {
+        {
+            app.add_systems(OnEnter(GameState::Replays), setup_replays_screen);
+            app.add_systems(
+                Update,
+                handle_record_buttons.run_if(in_state(GameState::Replays)),
+            );
+            app.add_systems(
+                OnExit(GameState::Replays),
+                tear_down_with_component::<ReplaysRoot>,
+            );
+
+            app.add_systems(OnEnter(GameState::Replay), setup_replay_ui);
+            app.add_systems(
+                Update,
+                (
+                    handle_replay_buttons,
+                    update_replay_ui.run_if(
+                        resource_exists_and_changed::<Replay>
+                            .or_else(any_component_changed::<Interaction>),
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Replay)),
+            );
+            app.add_systems(
+                OnExit(GameState::Replay),
+                tear_down_with_component::<ReplayRoot>,
+            );
+        }
+    }
+}
+
+#[derive(Component)]
+struct ReplaysRoot;
+
+#[derive(Component)]
+struct RecordButton(PathBuf);
+
+#[derive(Component)]
+struct ImportErrorText;
+
+#[derive(Component)]
+struct ReplayRoot;
+
+#[derive(Clone, Component, Copy)]
+enum ReplayButton {
+    First,
+    Previous,
+    Play,
+    Next,
+    Last,
+    Speed,
+    /// Shows the board after this number of moves.
+    Move(usize),
+}
+
+fn setup_replays_screen(mut commands: Commands) {
+    let text_style = TextStyle {
+        font_size: 40.0,
+        color: Color::BLACK,
+        ..default()
+    };
+    let root = commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert((ReplaysRoot, Name::new("UIRoot")))
+        .id();
+
+    let records = NotatedGame::exported();
+    let title = if records.is_empty() {
+        "No game exported yet"
+    } else {
+        "Pick a game to replay"
+    };
+    commands
+        .spawn(TextBundle::from_section(title, text_style.clone()))
+        .set_parent(root);
+    for path in records.into_iter().take(MAX_LISTED_RECORDS) {
+        let name = path
+            .file_name()
+            .map_or(String::new(), |name| name.to_string_lossy().into_owned());
+        commands
+            .spawn(ButtonBundle {
+                style: Style {
+                    margin: UiRect::top(Val::Px(5.0)),
+                    padding: UiRect::all(Val::Px(5.0)),
+                    ..default()
+                },
+                background_color: BUTTON_BG_COLOR.into(),
+                ..default()
+            })
+            .insert(RecordButton(path))
+            .with_children(|parent| {
+                parent.spawn(TextBundle::from_section(
+                    name,
+                    TextStyle {
+                        font_size: REPLAY_FONT_SIZE,
+                        ..text_style.clone()
+                    },
+                ));
+            })
+            .set_parent(root);
+    }
+    commands
+        .spawn(TextBundle::from_section(
+            String::new(),
+            TextStyle {
+                font_size: REPLAY_FONT_SIZE,
+                color: Color::RED,
+                ..default()
+            },
+        ))
+        .insert(ImportErrorText)
+        .set_parent(root);
+}
+
+/// Opens the clicked record, or tells what is wrong with it.
+fn handle_record_buttons(
+    mut commands: Commands,
+    mut state: ResMut<NextState<GameState>>,
+    mut buttons: Query<(&RecordButton, &Interaction, &mut BackgroundColor), Changed<Interaction>>,
+    mut error_texts: Query<&mut Text, With<ImportErrorText>>,
+) {
+    for (button, interaction, mut background_color) in &mut buttons {
+        match interaction {
+            Interaction::Pressed => match NotatedGame::import(&button.0) {
+                Ok(game) => {
+                    info!("replaying {}", button.0.display());
+                    commands.insert_resource(Replay::new(game));
+                    state.set(GameState::Replay);
+                }
+                Err(e) => {
+                    error!("Failed to import {}: {e}", button.0.display());
+                    for mut text in &mut error_texts {
+                        text.sections[0].value = e.to_string();
+                    }
+                }
+            },
+            Interaction::Hovered => *background_color = HOVER_BG_COLOR.into(),
+            Interaction::None => *background_color = BUTTON_BG_COLOR.into(),
+        }
+    }
+}
+
+fn setup_replay_ui(mut commands: Commands, replay: Res<Replay>) {
+    let text_style = TextStyle {
+        font_size: REPLAY_FONT_SIZE,
+        color: TEXT_COLOR,
+        ..default()
+    };
+    let button_bundle = ButtonBundle {
+        style: Style {
+            margin: UiRect::all(Val::Px(2.0)),
+            padding: UiRect::all(Val::Px(5.0)),
+            ..default()
+        },
+        background_color: BUTTON_BG_COLOR.into(),
+        ..default()
+    };
+    let game = replay.game();
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            z_index: ZIndex::Global(1),
+            ..default()
+        })
+        .insert(ReplayRoot)
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section(
+                    format!(
+                        "{} vs {} - {} - {} {}",
+                        game.cross, game.nought, game.result, game.mode, game.date
+                    ),
+                    text_style.clone(),
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    left: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    ..default()
+                }),
+            );
+
+            // Controls at the bottom of the screen.
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        left: Val::Px(10.0),
+                        bottom: Val::Px(10.0),
+                        ..default()
+                    },
+                    ..default()
+                })
+                .with_children(|parent| {
+                    for button in [
+                        ReplayButton::First,
+                        ReplayButton::Previous,
+                        ReplayButton::Play,
+                        ReplayButton::Next,
+                        ReplayButton::Last,
+                        ReplayButton::Speed,
+                    ] {
+                        let label = match button {
+                            ReplayButton::First => "|<",
+                            ReplayButton::Previous => "<",
+                            ReplayButton::Next => ">",
+                            ReplayButton::Last => ">|",
+                            // Updated from the replay.
+                            _ => "",
+                        };
+                        parent
+                            .spawn(button_bundle.clone())
+                            .insert(button)
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                            });
+                    }
+                });
+
+            // Move list on the right, clicking a move shows the board after it.
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        position_type: PositionType::Absolute,
+                        right: Val::Px(10.0),
+                        top: Val::Px(10.0),
+                        flex_direction: FlexDirection::Column,
+                        ..default()
+                    },
+                    background_color: Color::GRAY.with_a(0.3).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    let moves = std::iter::once("Start".to_string()).chain(
+                        game.moves.iter().enumerate().map(|(index, &cell)| {
+                            if index % 2 == 0 {
+                                format!("{}. {}", index / 2 + 1, square(cell))
+                            } else {
+                                format!("{}... {}", index / 2 + 1, square(cell))
+                            }
+                        }),
+                    );
+                    for (moves, label) in moves.enumerate() {
+                        parent
+                            .spawn(button_bundle.clone())
+                            .insert(ReplayButton::Move(moves))
+                            .with_children(|parent| {
+                                parent.spawn(TextBundle::from_section(label, text_style.clone()));
+                            });
+                    }
+                });
+        });
+}
+
+fn handle_replay_buttons(
+    mut replay: ResMut<Replay>,
+    buttons: Query<(&ReplayButton, &Interaction), Changed<Interaction>>,
+) {
+    for (&button, interaction) in &buttons {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+        match button {
+            ReplayButton::First => replay.go_to(0),
+            ReplayButton::Previous => replay.previous(),
+            ReplayButton::Play => replay.toggle_playing(),
+            ReplayButton::Next => replay.next(),
+            ReplayButton::Last => replay.go_to(usize::MAX),
+            ReplayButton::Speed => replay.next_speed(),
+            ReplayButton::Move(moves) => replay.go_to(moves),
+        }
+    }
+}
+
+/// Shows whether the replay is playing, its speed, the current move of the list and the hovered button.
+fn update_replay_ui(
+    replay: Res<Replay>,
+    mut buttons: Query<(&ReplayButton, &Interaction, &Children, &mut BackgroundColor)>,
+    mut texts: Query<&mut Text>,
+) {
+    for (&button, interaction, children, mut background_color) in &mut buttons {
+        let label = match button {
+            ReplayButton::Play if replay.is_playing() => Some("Pause".to_string()),
+            ReplayButton::Play => Some("Play".to_string()),
+            ReplayButton::Speed => Some(format!("{}x", replay.speed())),
+            _ => None,
+        };
+        if let Some(label) = label {
+            let mut texts = texts.iter_many_mut(children);
+            while let Some(mut text) = texts.fetch_next() {
+                text.sections[0].value.clone_from(&label);
+            }
+        }
+
+        *background_color = match (button, interaction) {
+            (ReplayButton::Move(moves), _) if moves == replay.shown() => CURRENT_MOVE_BG_COLOR,
+            (_, Interaction::Hovered | Interaction::Pressed) => HOVER_BG_COLOR,
+            _ => BUTTON_BG_COLOR,
+        }
+        .into();
+    }
+}
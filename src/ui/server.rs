@@ -1,6 +1,12 @@
 use bevy::prelude::*;
 
-use crate::{state::GameState, utils::tear_down_with_component};
+use crate::{
+    profile::Profile,
+    resources::MatchPassword,
+    state::GameState,
+    ui::text_input::{text_input_bundle, Focused, TextInput},
+    utils::tear_down_with_component,
+};
 
 pub struct ServerUiPlugin;
 
@@ -8,6 +14,10 @@ impl Plugin for ServerUiPlugin {
     fn build(&self, app: &mut App) {
         {
             app.add_systems(OnEnter(GameState::HostingLobby), setup_hosting_lobby);
+            app.add_systems(
+                Update,
+                update_match_password.run_if(in_state(GameState::HostingLobby)),
+            );
             app.add_systems(
                 OnExit(GameState::HostingLobby),
                 tear_down_with_component::<ServerWaitPlayerRoot>,
@@ -19,7 +29,14 @@ impl Plugin for ServerUiPlugin {
 #[derive(Component)]
 pub struct ServerWaitPlayerRoot;
 
-fn setup_hosting_lobby(mut commands: Commands) {
+#[derive(Component)]
+struct MatchPasswordTextEdit;
+
+fn setup_hosting_lobby(
+    mut commands: Commands,
+    password: Res<MatchPassword>,
+    profile: Res<Profile>,
+) {
     let text_style = TextStyle {
         font_size: 40.0,
         color: Color::BLACK,
@@ -42,8 +59,51 @@ fn setup_hosting_lobby(mut commands: Commands) {
 
     commands
         .spawn(TextBundle {
-            text: Text::from_section("Waiting for players to join...", text_style),
+            text: Text::from_section("Waiting for players to join...", text_style.clone()),
+            ..Default::default()
+        })
+        .set_parent(root);
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section(
+                format!(
+                    "Hosting as {} ({})",
+                    profile.name_or("Host"),
+                    profile.rating
+                ),
+                TextStyle {
+                    color: profile.avatar_color.color(),
+                    ..text_style.clone()
+                },
+            ),
+            ..Default::default()
+        })
+        .set_parent(root);
+    commands
+        .spawn(TextBundle {
+            text: Text::from_section("Password (empty for a public game)", text_style),
+            style: Style {
+                margin: UiRect::top(Val::Px(50.0)),
+                ..default()
+            },
             ..Default::default()
         })
         .set_parent(root);
+    commands
+        .spawn(text_input_bundle(
+            TextInput::new(password.clone().unwrap_or_default()).masked(),
+            300.0,
+        ))
+        .insert((MatchPasswordTextEdit, Focused))
+        .set_parent(root);
+}
+
+/// The password is checked when clients connect, so it can be changed while waiting.
+fn update_match_password(
+    inputs: Query<&TextInput, (With<MatchPasswordTextEdit>, Changed<TextInput>)>,
+    mut password: ResMut<MatchPassword>,
+) {
+    for input in &inputs {
+        **password = Some(input.value().to_string()).filter(|password| !password.is_empty());
+    }
 }
@@ -0,0 +1,124 @@
+use bevy::prelude::*;
+
+use crate::{
+    profile::Profile,
+    state::GameState,
+    stats::{GameResult, Statistics},
+    utils::tear_down_with_component,
+};
+
+pub struct StatsUiPlugin;
+
+impl Plugin for StatsUiPlugin {
+    fn build(&self, app: &mut App) {
+        {
+            app.add_systems(OnEnter(GameState::Statistics), setup_stats_screen);
+            app.add_systems(
+                OnExit(GameState::Statistics),
+                tear_down_with_component::<StatsRoot>,
+            );
+        }
+    }
+}
+
+#[derive(Component)]
+struct StatsRoot;
+
+fn setup_stats_screen(mut commands: Commands, statistics: Res<Statistics>, profile: Res<Profile>) {
+    let title_style = TextStyle {
+        font_size: 40.0,
+        color: Color::BLACK,
+        ..default()
+    };
+    let text_style = TextStyle {
+        font_size: 25.0,
+        color: Color::BLACK,
+        ..default()
+    };
+    let section_style = Style {
+        margin: UiRect::top(Val::Px(20.0)),
+        ..default()
+    };
+
+    let root = commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert((StatsRoot, Name::new("UIRoot")))
+        .id();
+
+    let mut overview = vec![format!("Rating: {}", profile.rating)];
+    overview.extend(overview_lines(&statistics));
+    let mut sections = vec![("Statistics".to_string(), overview)];
+    if !statistics.games().is_empty() {
+        let by_mode = statistics
+            .by_mode()
+            .into_iter()
+            .map(|(mode, tally)| format!("{mode}: {tally}"))
+            .collect();
+        sections.push(("By mode".to_string(), by_mode));
+        let by_opponent = statistics
+            .by_opponent()
+            .into_iter()
+            .map(|(opponent, tally)| format!("{opponent}: {tally}"))
+            .collect();
+        sections.push(("Head to head".to_string(), by_opponent));
+    }
+
+    for (title, lines) in sections {
+        commands
+            .spawn(
+                TextBundle::from_section(title, title_style.clone())
+                    .with_style(section_style.clone()),
+            )
+            .set_parent(root);
+        for line in lines {
+            commands
+                .spawn(TextBundle::from_section(line, text_style.clone()))
+                .set_parent(root);
+        }
+    }
+    commands
+        .spawn(
+            TextBundle::from_section("Press Escape to go back", text_style)
+                .with_style(section_style),
+        )
+        .set_parent(root);
+}
+
+/// Overall tally, streaks and average length of the games.
+fn overview_lines(statistics: &Statistics) -> Vec<String> {
+    let overall = statistics.overall();
+    if overall.games() == 0 {
+        return vec!["No game finished yet".to_string()];
+    }
+
+    let mut lines = vec![format!("{} games: {overall}", overall.games())];
+    if let Some((result, count)) = statistics.current_streak() {
+        let result = match result {
+            GameResult::Win => "won",
+            GameResult::Loss => "lost",
+            GameResult::Draw => "drawn",
+        };
+        lines.push(format!("Current streak: {count} {result}"));
+    }
+    lines.push(format!(
+        "Longest winning streak: {}",
+        statistics.longest_win_streak()
+    ));
+    if let Some((moves, duration)) = statistics.average_length() {
+        lines.push(format!(
+            "Average game: {moves:.1} moves, {}s",
+            duration.as_secs()
+        ));
+    }
+    lines
+}
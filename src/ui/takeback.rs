@@ -0,0 +1,167 @@
+use bevy::prelude::*;
+use bevy_replicon::{prelude::*, renet::transport::NetcodeClientTransport};
+
+use crate::{
+    components::{MatchState, MatchStatus, Player, Symbol},
+    events::Takeback,
+    state::GameState,
+    ui::{BUTTON_BG_COLOR, FONT_SIZE, HOVER_BG_COLOR},
+    utils::{board_shown, tear_down_with_component},
+    TEXT_COLOR,
+};
+
+pub struct TakebackUiPlugin;
+
+impl Plugin for TakebackUiPlugin {
+    fn build(&self, app: &mut App) {
+        {
+            app.add_systems(OnEnter(GameState::Playing), setup_takeback_ui);
+            app.add_systems(
+                Update,
+                (handle_takeback_buttons, update_takeback_ui).run_if(board_shown),
+            );
+            // Like the board, it stays over the game over screens to take back the last move.
+            for state in [
+                GameState::MainMenu,
+                GameState::Reconnecting,
+                GameState::Disconnected,
+            ] {
+                app.add_systems(OnEnter(state), tear_down_with_component::<TakebackRoot>);
+            }
+        }
+    }
+}
+
+#[derive(Component)]
+struct TakebackRoot;
+
+#[derive(Component)]
+struct TakebackText;
+
+#[derive(Clone, Component, Copy)]
+struct TakebackButton(Takeback);
+
+fn setup_takeback_ui(mut commands: Commands, roots: Query<(), With<TakebackRoot>>) {
+    if !roots.is_empty() {
+        return;
+    }
+
+    let text_style = TextStyle {
+        font_size: FONT_SIZE / 1.5,
+        color: TEXT_COLOR,
+        ..default()
+    };
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                left: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                ..default()
+            },
+            z_index: ZIndex::Global(1),
+            ..default()
+        })
+        .insert(TakebackRoot)
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(String::new(), text_style.clone()),
+                TakebackText,
+            ));
+            parent.spawn(NodeBundle::default()).with_children(|parent| {
+                for (text, takeback) in [
+                    ("Take back", Takeback::Request),
+                    ("Accept", Takeback::Accept),
+                    ("Decline", Takeback::Decline),
+                ] {
+                    parent
+                        .spawn(ButtonBundle {
+                            style: Style {
+                                display: Display::None,
+                                padding: UiRect::all(Val::Px(5.0)),
+                                ..default()
+                            },
+                            background_color: BUTTON_BG_COLOR.into(),
+                            ..default()
+                        })
+                        .insert(TakebackButton(takeback))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(text, text_style.clone()));
+                        });
+                }
+            });
+        });
+}
+
+fn handle_takeback_buttons(
+    mut buttons: Query<(&TakebackButton, &Interaction, &mut BackgroundColor), Changed<Interaction>>,
+    mut takeback_events: EventWriter<Takeback>,
+) {
+    for (button, interaction, mut background_color) in &mut buttons {
+        match interaction {
+            Interaction::Pressed => {
+                takeback_events.send(button.0);
+            }
+            Interaction::Hovered => *background_color = HOVER_BG_COLOR.into(),
+            Interaction::None => *background_color = BUTTON_BG_COLOR.into(),
+        }
+    }
+}
+
+/// Offers the player of the last move to take it back, and its opponent to answer a request.
+fn update_takeback_ui(
+    client_transport: Option<Res<NetcodeClientTransport>>,
+    match_state: Query<&MatchState>,
+    players: Query<(&Player, &Symbol)>,
+    mut texts: Query<&mut Text, With<TakebackText>>,
+    mut buttons: Query<(&TakebackButton, &mut Style)>,
+) {
+    let client_id = client_transport
+        .map(|client| client.client_id())
+        .unwrap_or(SERVER_ID);
+    let plays = |symbol: Symbol| {
+        players
+            .iter()
+            .any(|(player, &s)| player.client_id() == client_id && s == symbol)
+    };
+
+    let (can_request, answering, waiting) = match match_state.get_single() {
+        Ok(match_state) => {
+            let forfeited = matches!(match_state.status(), MatchStatus::Won { .. })
+                && match_state.winning_line().is_none();
+            let request = match_state.takeback_request();
+            let answering = request.is_some_and(|symbol| plays(symbol.next()));
+            (
+                !forfeited && request.is_none() && match_state.last_player().is_some_and(plays),
+                answering,
+                !answering && request.is_some_and(plays),
+            )
+        }
+        Err(_) => (false, false, false),
+    };
+
+    let message = if answering {
+        "Your opponent asks to take back their last move."
+    } else if waiting {
+        "Waiting for your opponent to accept the takeback..."
+    } else {
+        ""
+    };
+    for mut text in &mut texts {
+        if text.sections[0].value != message {
+            text.sections[0].value = message.to_string();
+        }
+    }
+
+    for (button, mut style) in &mut buttons {
+        let shown = match button.0 {
+            Takeback::Request => can_request,
+            Takeback::Accept | Takeback::Decline => answering,
+        };
+        let display = if shown { Display::Flex } else { Display::None };
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
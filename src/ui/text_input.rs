@@ -0,0 +1,173 @@
+use bevy::{prelude::*, window::ReceivedCharacter};
+
+use super::FONT_SIZE;
+
+pub struct TextInputPlugin;
+
+impl Plugin for TextInputPlugin {
+    fn build(&self, app: &mut App) {
+        {
+            app.add_event::<TextInputSubmit>();
+            app.add_systems(
+                Update,
+                (focus_text_input, type_text_input, render_text_input).chain(),
+            );
+        }
+    }
+}
+
+/// A single line text field that receives the typed characters while it has [`Focused`].
+#[derive(Component)]
+pub struct TextInput {
+    value: String,
+    filter: fn(char) -> bool,
+    max_chars: usize,
+    masked: bool,
+}
+
+impl TextInput {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self {
+            value: value.into(),
+            filter: |c| !c.is_control(),
+            max_chars: 32,
+            masked: false,
+        }
+    }
+
+    /// Only accepts the characters for which `filter` returns `true`.
+    pub fn with_filter(mut self, filter: fn(char) -> bool) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    /// Limits the length of the value, the default is 32 characters.
+    pub fn with_max_chars(mut self, max_chars: usize) -> Self {
+        self.max_chars = max_chars;
+        self
+    }
+
+    /// Hides the typed characters, for passwords.
+    pub fn masked(mut self) -> Self {
+        self.masked = true;
+        self
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn set_value(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+    }
+}
+
+/// Marks the text input that receives the keyboard.
+#[derive(Component)]
+pub struct Focused;
+
+/// Sent when Enter is pressed in the focused text input.
+#[derive(Event)]
+pub struct TextInputSubmit;
+
+/// Creates a text input of the given width that is focused by clicking on it.
+pub fn text_input_bundle(input: TextInput, width: f32) -> impl Bundle {
+    (
+        TextBundle {
+            text: Text::from_section(
+                String::new(),
+                TextStyle {
+                    font_size: FONT_SIZE,
+                    color: Color::DARK_GRAY,
+                    ..default()
+                },
+            ),
+            style: Style {
+                width: Val::Px(width),
+                min_height: Val::Px(FONT_SIZE),
+                ..default()
+            },
+            background_color: Color::GRAY.into(),
+            ..default()
+        },
+        Interaction::default(),
+        input,
+    )
+}
+
+/// Focuses the clicked text input, Tab moves the focus to the next one.
+fn focus_text_input(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    clicked: Query<(Entity, &Interaction), (Changed<Interaction>, With<TextInput>)>,
+    inputs: Query<(Entity, Has<Focused>), With<TextInput>>,
+) {
+    let mut focus = clicked
+        .iter()
+        .find(|(_, &interaction)| interaction == Interaction::Pressed)
+        .map(|(entity, _)| entity);
+
+    if focus.is_none() && keyboard_input.just_pressed(KeyCode::Tab) {
+        let mut entities: Vec<_> = inputs.iter().collect();
+        entities.sort_by_key(|&(entity, _)| entity);
+        let next = entities
+            .iter()
+            .position(|&(_, focused)| focused)
+            .map_or(0, |index| (index + 1) % entities.len());
+        focus = entities.get(next).map(|&(entity, _)| entity);
+    }
+
+    let Some(focus) = focus else {
+        return;
+    };
+    for (entity, focused) in &inputs {
+        if entity == focus {
+            commands.entity(entity).insert(Focused);
+        } else if focused {
+            commands.entity(entity).remove::<Focused>();
+        }
+    }
+}
+
+fn type_text_input(
+    mut characters: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut submit_events: EventWriter<TextInputSubmit>,
+    mut focused: Query<&mut TextInput, With<Focused>>,
+) {
+    let Ok(mut input) = focused.get_single_mut() else {
+        characters.clear();
+        return;
+    };
+
+    for event in characters.read() {
+        for c in event.char.chars() {
+            if (input.filter)(c) && input.value.chars().count() < input.max_chars {
+                input.value.push(c);
+            }
+        }
+    }
+    if keyboard_input.just_pressed(KeyCode::Backspace) {
+        input.value.pop();
+    }
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        submit_events.send(TextInputSubmit);
+    }
+}
+
+/// Displays the value of the inputs with a caret on the focused one.
+fn render_text_input(mut inputs: Query<(&TextInput, &mut Text, Has<Focused>)>) {
+    for (input, mut text, focused) in &mut inputs {
+        let mut value = if input.masked {
+            "*".repeat(input.value.chars().count())
+        } else {
+            input.value.clone()
+        };
+        if focused {
+            value.push('|');
+        }
+        if text.sections[0].value != value {
+            text.sections[0].value = value;
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::{events::MoveRejected, state::GameState, utils::tear_down_with_component, TEXT_COLOR};
+
+use super::FONT_SIZE;
+
+/// How long a toast stays on screen.
+const TOAST_DURATION: Duration = Duration::from_secs(2);
+
+pub struct ToastUiPlugin;
+
+impl Plugin for ToastUiPlugin {
+    fn build(&self, app: &mut App) {
+        {
+            app.add_systems(
+                Update,
+                (
+                    show_move_rejections.run_if(on_event::<MoveRejected>()),
+                    expire_toasts,
+                )
+                    .chain(),
+            );
+            app.add_systems(
+                OnEnter(GameState::MainMenu),
+                tear_down_with_component::<Toast>,
+            );
+        }
+    }
+}
+
+/// A short message over the board, removed when its timer finishes.
+#[derive(Component, Deref, DerefMut)]
+struct Toast(Timer);
+
+/// Tells the player why the server ignored the cell they picked, replacing the previous toast.
+fn show_move_rejections(
+    mut commands: Commands,
+    mut rejected_events: EventReader<MoveRejected>,
+    toasts: Query<Entity, With<Toast>>,
+) {
+    let Some(event) = rejected_events.read().last() else {
+        return;
+    };
+    for toast_entity in &toasts {
+        commands.entity(toast_entity).despawn_recursive();
+    }
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                top: Val::Px(60.0),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            z_index: ZIndex::Global(3),
+            ..default()
+        })
+        .insert(Toast(Timer::new(TOAST_DURATION, TimerMode::Once)))
+        .with_children(|parent| {
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        padding: UiRect::all(Val::Px(8.0)),
+                        ..default()
+                    },
+                    background_color: Color::BLACK.with_a(0.7).into(),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        event.reason().to_string(),
+                        TextStyle {
+                            font_size: FONT_SIZE / 1.5,
+                            color: TEXT_COLOR,
+                            ..default()
+                        },
+                    ));
+                });
+        });
+}
+
+fn expire_toasts(mut commands: Commands, time: Res<Time>, mut toasts: Query<(Entity, &mut Toast)>) {
+    for (toast_entity, mut timer) in &mut toasts {
+        if timer.tick(time.delta()).finished() {
+            commands.entity(toast_entity).despawn_recursive();
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 
 use crate::{
+    components::{
+        AvatarColor, Away, Clock, MatchState, PlayerName, Rating, Score, Series, Spectator, Symbol,
+    },
     resources::{CurrentTurn, SymbolFont},
     state::GameState,
     ui::{FONT_SIZE, SYMBOL_SECTION},
@@ -16,8 +19,12 @@ impl Plugin for TurnUiPlugin {
             app.add_systems(OnEnter(GameState::Playing), setup_turn_ui);
             app.add_systems(
                 PostUpdate,
-                symbol_turn_text_system
-                    .run_if(resource_changed::<CurrentTurn>)
+                (
+                    symbol_turn_text_system,
+                    matchup_text_system,
+                    series_text_system,
+                    clock_text_system,
+                )
                     .run_if(in_state(GameState::Playing)),
             );
             app.add_systems(
@@ -34,7 +41,65 @@ struct TurnUiRoot;
 #[derive(Component)]
 struct BottomText;
 
+#[derive(Component)]
+struct MatchupText;
+
+#[derive(Component)]
+struct SeriesText;
+
+#[derive(Component)]
+struct ClockText;
+
 fn setup_turn_ui(mut commands: Commands, symbol_font: Res<SymbolFont>) {
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                top: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            ..default()
+        })
+        .insert(TurnUiRoot)
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(
+                    String::new(),
+                    TextStyle {
+                        font_size: FONT_SIZE / 1.5,
+                        color: TEXT_COLOR,
+                        ..Default::default()
+                    },
+                ),
+                MatchupText,
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    String::new(),
+                    TextStyle {
+                        font_size: FONT_SIZE / 1.5,
+                        color: TEXT_COLOR,
+                        ..Default::default()
+                    },
+                ),
+                SeriesText,
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    String::new(),
+                    TextStyle {
+                        font_size: FONT_SIZE / 1.5,
+                        color: TEXT_COLOR,
+                        ..Default::default()
+                    },
+                ),
+                ClockText,
+            ));
+        });
     let container = commands
         .spawn(NodeBundle {
             style: Style {
@@ -74,11 +139,132 @@ fn setup_turn_ui(mut commands: Commands, symbol_font: Res<SymbolFont>) {
         .set_parent(container);
 }
 
+/// Shows whose turn it is, in the avatar colour of the player.
 fn symbol_turn_text_system(
     mut bottom_text: Query<&mut Text, With<BottomText>>,
     current_turn: Res<CurrentTurn>,
+    match_state: Query<&MatchState>,
+    players: Query<(&PlayerName, &AvatarColor, &Symbol)>,
 ) {
-    let symbol_section = &mut bottom_text.single_mut().sections[SYMBOL_SECTION];
-    symbol_section.value = current_turn.symbol().glyph().into();
-    symbol_section.style.color = current_turn.symbol().color();
+    let Ok(mut bottom_text) = bottom_text.get_single_mut() else {
+        return;
+    };
+    let symbol = current_turn.symbol();
+    let move_number = match_state
+        .get_single()
+        .map_or(1, |match_state| match_state.move_number() + 1);
+    let (value, color) = match players
+        .iter()
+        .find(|(.., &player_symbol)| player_symbol == symbol)
+    {
+        Some((name, avatar_color, _)) => (
+            format!("Move {move_number}, {}'s turn: ", name.name()),
+            avatar_color.color(),
+        ),
+        None => (format!("Move {move_number}, current turn: "), TEXT_COLOR),
+    };
+
+    let text_section = &mut bottom_text.sections[0];
+    if text_section.value != value || text_section.style.color != color {
+        text_section.value = value;
+        text_section.style.color = color;
+    }
+    let symbol_section = &mut bottom_text.sections[SYMBOL_SECTION];
+    if symbol_section.value != symbol.glyph() {
+        symbol_section.value = symbol.glyph().into();
+        symbol_section.style.color = symbol.color();
+    }
+}
+
+/// Shows the seated players with their ratings, whether they are reconnecting and how many
+/// spectators are watching.
+fn matchup_text_system(
+    mut matchup_text: Query<&mut Text, With<MatchupText>>,
+    players: Query<(&PlayerName, &Symbol, Option<&Rating>, Has<Away>)>,
+    spectators: Query<(), With<Spectator>>,
+) {
+    let mut players: Vec<_> = players.iter().collect();
+    players.sort_by_key(|(_, &symbol, ..)| symbol != Symbol::Cross);
+    let mut matchup = players
+        .iter()
+        .map(|(name, symbol, rating, away)| {
+            let mut details = symbol.to_string();
+            if let Some(rating) = rating {
+                details.push_str(&format!(", {}", rating.value()));
+            }
+            if *away {
+                details.push_str(", reconnecting...");
+            }
+            format!("{} ({details})", name.name())
+        })
+        .collect::<Vec<_>>()
+        .join(" vs ");
+    match spectators.iter().count() {
+        0 => {}
+        1 => matchup.push_str(" - 1 spectator"),
+        count => matchup.push_str(&format!(" - {count} spectators")),
+    }
+
+    let Ok(mut text) = matchup_text.get_single_mut() else {
+        return;
+    };
+    if text.sections[0].value != matchup {
+        text.sections[0].value = matchup;
+    }
+}
+
+/// Shows the game being played and the score of the series.
+fn series_text_system(
+    mut series_text: Query<&mut Text, With<SeriesText>>,
+    series: Query<&Series>,
+    players: Query<(&PlayerName, &Symbol, &Score)>,
+) {
+    let value = match series.get_single() {
+        Ok(series) => {
+            let mut players: Vec<_> = players.iter().collect();
+            players.sort_by_key(|(_, &symbol, _)| symbol != Symbol::Cross);
+            let score = players
+                .iter()
+                .map(|(name, _, score)| format!("{} {}", name.name(), score.wins()))
+                .collect::<Vec<_>>()
+                .join(" - ");
+            format!(
+                "Game {} of best of {}: {score}",
+                series.game(),
+                series.best_of()
+            )
+        }
+        Err(_) => String::new(),
+    };
+
+    let Ok(mut text) = series_text.get_single_mut() else {
+        return;
+    };
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
+    }
+}
+
+/// Shows the time left to both players of a timed match.
+fn clock_text_system(
+    mut clock_text: Query<&mut Text, With<ClockText>>,
+    players: Query<(&PlayerName, &Symbol, &Clock)>,
+) {
+    let mut players: Vec<_> = players.iter().collect();
+    players.sort_by_key(|(_, &symbol, _)| symbol != Symbol::Cross);
+    let clocks = players
+        .iter()
+        .map(|(name, _, clock)| {
+            let seconds = clock.remaining().as_secs_f32().ceil() as u64;
+            format!("{} {}:{:02}", name.name(), seconds / 60, seconds % 60)
+        })
+        .collect::<Vec<_>>()
+        .join("   ");
+
+    let Ok(mut text) = clock_text.get_single_mut() else {
+        return;
+    };
+    if text.sections[0].value != clocks {
+        text.sections[0].value = clocks;
+    }
 }
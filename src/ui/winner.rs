@@ -1,17 +1,36 @@
 use bevy::prelude::*;
 use bevy_replicon::{prelude::*, renet::transport::NetcodeClientTransport};
 
-use crate::{resources::Winner, state::GameState, utils::tear_down_with_component};
+use crate::{
+    components::{MatchState, MatchStatus, Player, PlayerName, Score, Series, Symbol},
+    events::RematchRequest,
+    notation::{ExportGame, GameExported},
+    resources::Winner,
+    state::GameState,
+    utils::tear_down_with_component,
+};
+
+use super::{BUTTON_BG_COLOR, HOVER_BG_COLOR};
 
 pub struct WinnerPlugin;
 
 impl Plugin for WinnerPlugin {
     fn build(&self, app: &mut App) {
         {
-            app.add_systems(OnEnter(GameState::GameOver), setup_winner_ui);
+            for state in [GameState::GameOver, GameState::Draw] {
+                app.add_systems(OnEnter(state), setup_winner_ui);
+                app.add_systems(OnExit(state), tear_down_with_component::<WinnerRoot>);
+            }
             app.add_systems(
-                OnExit(GameState::GameOver),
-                tear_down_with_component::<WinnerRoot>,
+                Update,
+                (
+                    handle_rematch_button,
+                    handle_export_button,
+                    score_text_system,
+                    rematch_ui_system,
+                    export_text_system.run_if(on_event::<GameExported>()),
+                )
+                    .run_if(in_state(GameState::GameOver).or_else(in_state(GameState::Draw))),
             );
         }
     }
@@ -20,29 +39,56 @@ impl Plugin for WinnerPlugin {
 #[derive(Component)]
 struct WinnerRoot;
 
+#[derive(Component)]
+struct ScoreText;
+
+#[derive(Component)]
+struct RematchText;
+
+#[derive(Component)]
+struct RematchButton;
+
+#[derive(Component)]
+struct ExportButton;
+
+#[derive(Component)]
+struct ExportText;
+
 fn setup_winner_ui(
     mut commands: Commands,
     winner: Res<Winner>,
     client_transport: Option<Res<NetcodeClientTransport>>,
+    series: Query<&Series>,
+    match_state: Query<&MatchState>,
+    players: Query<(&PlayerName, &Score, &Symbol)>,
 ) {
     let current_player = client_transport
         .as_ref()
         .map(|client| client.client_id())
         .unwrap_or(SERVER_ID);
 
-    let game_over_message = match &winner.client_id() {
-        Some(winner) => {
-            if *winner == current_player {
-                "You won!".to_string()
-            } else {
-                "You lost!".to_string()
-            }
-        }
-        None => "It's a draw!".to_string(),
+    let winner_name = match match_state.get_single().map(MatchState::status) {
+        Ok(MatchStatus::Won { symbol, .. }) => players
+            .iter()
+            .find(|(_, _, &player_symbol)| player_symbol == symbol)
+            .map(|(name, ..)| name.name()),
+        _ => None,
+    };
+    let game_over_message = match (winner.client_id(), winner_name) {
+        (_, Some(name)) => format!("{name} wins!"),
+        (Some(winner), None) if winner == current_player => "You won!".to_string(),
+        (Some(_), None) => "You lost!".to_string(),
+        (None, _) => "It's a draw!".to_string(),
     };
 
     debug!("Game over message: {}", game_over_message);
 
+    let text_style = TextStyle {
+        font_size: 40.0,
+        color: Color::BLACK,
+        ..default()
+    };
+
     let root = commands
         .spawn(NodeBundle {
             style: Style {
@@ -86,17 +132,191 @@ fn setup_winner_ui(
             ..default()
         })
         .set_parent(container);
+    let series_winner = series.get_single().ok().and_then(|series| {
+        players
+            .iter()
+            .find(|(_, score, _)| series.is_won_with(score.wins()))
+    });
+    if let Some((name, ..)) = series_winner {
+        commands
+            .spawn(TextBundle::from_section(
+                format!("{} wins the series!", name.name()),
+                text_style.clone(),
+            ))
+            .set_parent(container);
+    }
+    commands
+        .spawn(TextBundle::from_section(String::new(), text_style.clone()))
+        .insert(ScoreText)
+        .set_parent(container);
+    commands
+        .spawn(ButtonBundle {
+            style: Style {
+                display: Display::None,
+                margin: UiRect::top(Val::Px(30.0)),
+                padding: UiRect::all(Val::Px(5.0)),
+                ..default()
+            },
+            background_color: BUTTON_BG_COLOR.into(),
+            ..default()
+        })
+        .insert(RematchButton)
+        .with_children(|parent| {
+            let label = if series_winner.is_some() {
+                "New series"
+            } else {
+                "Rematch"
+            };
+            parent.spawn(TextBundle::from_section(label, text_style.clone()));
+        })
+        .set_parent(container);
+    commands
+        .spawn(TextBundle::from_section(String::new(), text_style.clone()))
+        .insert(RematchText)
+        .set_parent(container);
+    commands
+        .spawn(ButtonBundle {
+            style: Style {
+                margin: UiRect::top(Val::Px(30.0)),
+                padding: UiRect::all(Val::Px(5.0)),
+                ..default()
+            },
+            background_color: BUTTON_BG_COLOR.into(),
+            ..default()
+        })
+        .insert(ExportButton)
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Export", text_style.clone()));
+        })
+        .set_parent(container);
+    commands
+        .spawn(TextBundle::from_section(String::new(), text_style.clone()))
+        .insert(ExportText)
+        .set_parent(container);
     commands
         .spawn(TextBundle {
-            text: Text::from_section(
-                "Press 'ESC' to return to the main menu.",
-                TextStyle {
-                    font_size: 40.0,
-                    color: Color::BLACK,
-                    ..default()
-                },
-            ),
+            text: Text::from_section("Press 'ESC' to return to the main menu.", text_style),
             ..default()
         })
         .set_parent(container);
 }
+
+fn handle_rematch_button(
+    mut buttons: Query<
+        (&Interaction, &mut BackgroundColor),
+        (With<RematchButton>, Changed<Interaction>),
+    >,
+    mut rematch_events: EventWriter<RematchRequest>,
+) {
+    for (interaction, mut background_color) in &mut buttons {
+        match interaction {
+            Interaction::Pressed => {
+                rematch_events.send(RematchRequest);
+            }
+            Interaction::Hovered => *background_color = HOVER_BG_COLOR.into(),
+            Interaction::None => *background_color = BUTTON_BG_COLOR.into(),
+        }
+    }
+}
+
+fn handle_export_button(
+    mut buttons: Query<
+        (&Interaction, &mut BackgroundColor),
+        (With<ExportButton>, Changed<Interaction>),
+    >,
+    mut export_events: EventWriter<ExportGame>,
+) {
+    for (interaction, mut background_color) in &mut buttons {
+        match interaction {
+            Interaction::Pressed => {
+                export_events.send(ExportGame);
+            }
+            Interaction::Hovered => *background_color = HOVER_BG_COLOR.into(),
+            Interaction::None => *background_color = BUTTON_BG_COLOR.into(),
+        }
+    }
+}
+
+/// Tells where the game record was written.
+fn export_text_system(
+    mut exported_events: EventReader<GameExported>,
+    mut export_text: Query<&mut Text, With<ExportText>>,
+) {
+    let Some(exported) = exported_events.read().last() else {
+        return;
+    };
+    let message = match &exported.path {
+        Some(path) => format!("Exported to {}", path.display()),
+        None => "The export failed, see the log.".to_string(),
+    };
+    if let Ok(mut text) = export_text.get_single_mut() {
+        text.sections[0].value = message;
+    }
+}
+
+/// Shows the wins of the players since they joined, or in the current series.
+fn score_text_system(
+    mut score_text: Query<&mut Text, With<ScoreText>>,
+    series: Query<&Series>,
+    players: Query<(Entity, &PlayerName, &Score)>,
+) {
+    let mut players: Vec<_> = players.iter().collect();
+    players.sort_by_key(|&(entity, ..)| entity);
+    let mut score = players
+        .iter()
+        .map(|(_, name, score)| format!("{} {}", name.name(), score.wins()))
+        .collect::<Vec<_>>()
+        .join(" - ");
+    if let Ok(series) = series.get_single() {
+        score = format!("Best of {}: {score}", series.best_of());
+    }
+
+    let Ok(mut text) = score_text.get_single_mut() else {
+        return;
+    };
+    if text.sections[0].value != score {
+        text.sections[0].value = score;
+    }
+}
+
+/// Offers a rematch to the players, telling whether the opponent already asked for it.
+fn rematch_ui_system(
+    client_transport: Option<Res<NetcodeClientTransport>>,
+    match_state: Query<&MatchState>,
+    players: Query<(&Player, &Symbol)>,
+    mut rematch_text: Query<&mut Text, With<RematchText>>,
+    mut rematch_button: Query<&mut Style, With<RematchButton>>,
+) {
+    let client_id = client_transport
+        .map(|client| client.client_id())
+        .unwrap_or(SERVER_ID);
+    let symbol = players
+        .iter()
+        .find(|(player, _)| player.client_id() == client_id)
+        .map(|(_, &symbol)| symbol);
+    let offer = match_state
+        .get_single()
+        .ok()
+        .and_then(|match_state| match_state.rematch_offer());
+
+    let (message, shown) = match (symbol, offer) {
+        (None, _) => ("", false),
+        (Some(symbol), Some(offer)) if offer == symbol => {
+            ("Waiting for your opponent to accept the rematch...", false)
+        }
+        (Some(_), Some(_)) => ("Your opponent wants a rematch.", true),
+        (Some(_), None) => ("", true),
+    };
+
+    if let Ok(mut text) = rematch_text.get_single_mut() {
+        if text.sections[0].value != message {
+            text.sections[0].value = message.to_string();
+        }
+    }
+    if let Ok(mut style) = rematch_button.get_single_mut() {
+        let display = if shown { Display::Flex } else { Display::None };
+        if style.display != display {
+            style.display = display;
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use crate::components::{Player, Symbol};
 use crate::resources::CurrentTurn;
+use crate::state::GameState;
 use bevy::prelude::*;
 use bevy_replicon::prelude::*;
 use bevy_replicon::renet::transport::NetcodeClientTransport;
@@ -19,11 +20,24 @@ pub fn local_player_turn(
         .any(|(player, &symbol)| player.client_id() == client_id && symbol == current_turn.symbol())
 }
 
+/// Returns `true` while the board is shown, during the match and behind the game over screens.
+pub fn board_shown(state: Res<State<GameState>>) -> bool {
+    matches!(
+        state.get(),
+        GameState::Playing | GameState::GameOver | GameState::Draw
+    )
+}
+
 /// A condition for systems to check if any component of type `T` was added to the world.
 pub fn any_component_added<T: Component>(components: Query<(), Added<T>>) -> bool {
     !components.is_empty()
 }
 
+/// A condition for systems to check if any component of type `T` was added or changed.
+pub fn any_component_changed<T: Component>(components: Query<(), Changed<T>>) -> bool {
+    !components.is_empty()
+}
+
 /// Destroys all entities with the component `T`.
 pub fn tear_down_with_component<T: Component>(
     mut commands: Commands,
@@ -33,9 +47,3 @@ pub fn tear_down_with_component<T: Component>(
         commands.entity(entity).despawn_recursive();
     }
 }
-
-pub fn inspect_and_log_error(result: Result<(), impl std::fmt::Debug>) {
-    if let Err(e) = result {
-        error!("Error: {:?}", e);
-    }
-}
\ No newline at end of file